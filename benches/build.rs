@@ -0,0 +1,92 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Creates pseudo-random 32-byte keys, by hashing consecutive integers.
+fn random_items(count: usize) -> Vec<(alloy_primitives::B256, Vec<u8>)> {
+  (0..count as u64)
+    .map(|i| {
+      let key = alloy_primitives::keccak256(i.to_be_bytes());
+      (key, i.to_be_bytes().to_vec())
+    })
+    .collect()
+}
+
+fn bench_sort(c: &mut Criterion) {
+  let mut group = c.benchmark_group("sort_by_nibbles");
+  for size in SIZES {
+    let items = random_items(size);
+    group.bench_with_input(BenchmarkId::from_parameter(size), &items, |b, items| {
+      b.iter(|| {
+        let mut sorted_items = items.iter().collect::<Vec<_>>();
+        sorted_items.sort_by_key(|(k, _)| alloy_trie::Nibbles::unpack(k));
+        black_box(sorted_items)
+      })
+    });
+  }
+  group.finish();
+}
+
+fn bench_alloy_build(c: &mut Criterion) {
+  let mut group = c.benchmark_group("alloy_build");
+  group.sample_size(10);
+  for size in SIZES {
+    let items = random_items(size);
+    group.bench_with_input(BenchmarkId::from_parameter(size), &items, |b, items| {
+      b.iter(|| black_box(risc0_mpt_collapse::build_alloy_trie_with_proof(items)))
+    });
+  }
+  group.finish();
+}
+
+fn bench_alloy_build_opts(c: &mut Criterion) {
+  let mut group = c.benchmark_group("alloy_build_opts");
+  group.sample_size(10);
+  let size = SIZES[SIZES.len() - 1];
+  let mut items = random_items(size);
+  items.sort();
+  for (name, skip_sort, retain_proofs) in [
+    ("full", false, true),
+    ("skip_sort", true, true),
+    ("root_only", false, false),
+    ("skip_sort_root_only", true, false),
+  ] {
+    let opts = risc0_mpt_collapse::BuildOpts {
+      skip_sort,
+      retain_proofs,
+      ..Default::default()
+    };
+    group.bench_with_input(BenchmarkId::new(name, size), &items, |b, items| {
+      b.iter(|| {
+        black_box(risc0_mpt_collapse::build_alloy_trie_with_proof_opts(
+          items, opts,
+        ))
+      })
+    });
+  }
+  group.finish();
+}
+
+fn bench_risc0_from_rlp(c: &mut Criterion) {
+  let mut group = c.benchmark_group("risc0_from_rlp");
+  group.sample_size(10);
+  for size in SIZES {
+    let (_, rlp_nodes) = risc0_mpt_collapse::build_alloy_trie_with_proof(&random_items(size));
+    group.bench_with_input(
+      BenchmarkId::from_parameter(size),
+      &rlp_nodes,
+      |b, rlp_nodes| b.iter(|| black_box(risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).unwrap())),
+    );
+  }
+  group.finish();
+}
+
+criterion_group!(
+  benches,
+  bench_sort,
+  bench_alloy_build,
+  bench_alloy_build_opts,
+  bench_risc0_from_rlp
+);
+criterion_main!(benches);
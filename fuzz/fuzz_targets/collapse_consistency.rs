@@ -0,0 +1,29 @@
+//! Builds a trie from arbitrary 32-byte keys, adds a removee key and removes it again from the
+//! Risc0 trie, checking that Risc0 and Alloy roots agree at every stage.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<[u8; 32]>, [u8; 32])| {
+  let (mut keys, removee_key) = input;
+
+  // Keys must be unique and must not contain the removee key.
+  keys.sort_unstable();
+  keys.dedup();
+  keys.retain(|key| *key != removee_key);
+  if keys.is_empty() {
+    return;
+  }
+
+  // Values do not affect the trie shape, so keys double as values.
+  let keys = keys
+    .into_iter()
+    .map(|key| (alloy_primitives::B256::from(key), key.to_vec()))
+    .collect::<Vec<_>>();
+  let removee_key = alloy_primitives::B256::from(removee_key);
+
+  if let Err(err) = risc0_mpt_collapse::assert_collapse_consistency(&keys, &removee_key) {
+    panic!("{err}");
+  }
+});
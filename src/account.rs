@@ -0,0 +1,452 @@
+//! Two-level state tries, where account leaves commit to the roots of their storage tries.
+
+use alloc::vec::Vec;
+use alloy_rlp::Encodable;
+
+/// Ethereum account, together with its storage slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountWithStorage {
+  /// Number of transactions sent (or contracts created) by the account.
+  pub nonce: u64,
+  /// Balance in wei.
+  pub balance: alloy_primitives::U256,
+  /// Hash of the account code, [`alloy_primitives::KECCAK256_EMPTY`] for accounts without code.
+  pub code_hash: alloy_primitives::B256,
+  /// Storage slots and their values - slots holding zero are treated as absent, as in Ethereum.
+  pub storage: Vec<(alloy_primitives::U256, alloy_primitives::U256)>,
+}
+
+/// Fields of an account leaf, as committed to by the state trie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Account {
+  /// Number of transactions sent (or contracts created) by the account.
+  pub nonce: u64,
+  /// Balance in wei.
+  pub balance: alloy_primitives::U256,
+  /// Root hash of the account storage trie, [`crate::EMPTY_ROOT`] for accounts without storage.
+  pub storage_root: alloy_primitives::B256,
+  /// Hash of the account code, [`alloy_primitives::KECCAK256_EMPTY`] for accounts without code.
+  pub code_hash: alloy_primitives::B256,
+}
+
+/// Builds an account trie from structured accounts, encoding their leaves consistently
+///
+/// Each account is keyed by [`crate::keys::account_key`] of its address, with RLP-encoded
+/// `[nonce, balance, storage_root, code_hash]` value - the same as in
+/// [`build_account_with_storage`], but for accounts with already known storage roots.
+#[derive(Debug, Clone, Default)]
+pub struct AccountTrieBuilder {
+  /// Hashed addresses and encoded accounts added so far, in any order.
+  items: Vec<(alloy_primitives::B256, Vec<u8>)>,
+}
+
+impl AccountTrieBuilder {
+  /// Creates a builder of an empty account trie.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds an account to the trie
+  ///
+  /// # Arguments
+  /// * `address` - Address of the account, hashed into its key
+  /// * `account` - Fields of the account leaf
+  pub fn add(&mut self, address: alloy_primitives::Address, account: Account) {
+    let key = crate::keys::account_key(address);
+    self.items.push((key, encode_account(&account)));
+  }
+
+  /// Builds the trie of all added accounts, consuming the builder
+  ///
+  /// # Returns
+  /// * State root and RLP-encoded proof nodes, or the empty root and no nodes if nothing was added
+  ///
+  /// # Panics
+  /// * If the same address was added more than once
+  pub fn finish(self) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+    crate::build_alloy_trie_with_proof(&self.items)
+  }
+}
+
+/// Storage trie of a single account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageTrieProof {
+  /// Address of the account owning the storage.
+  pub address: alloy_primitives::Address,
+  /// Root hash of the storage trie, embedded in the account leaf.
+  pub storage_root: alloy_primitives::B256,
+  /// RLP-encoded storage trie nodes, root node first.
+  pub nodes: Vec<alloy_primitives::Bytes>,
+}
+
+/// State trie with proofs for all accounts and all their storage slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateTrieProof {
+  /// Root hash of the account trie.
+  pub state_root: alloy_primitives::B256,
+  /// RLP-encoded account trie nodes, root node first.
+  pub account_nodes: Vec<alloy_primitives::Bytes>,
+  /// Storage trie of each account, in the order of accounts.
+  pub storage: Vec<StorageTrieProof>,
+}
+
+/// Builds storage tries of all accounts, and then the account trie committing to them
+///
+/// Storage trie is keyed by [`crate::keys::storage_key`] of each slot, with RLP-encoded values.
+/// Account trie is keyed by [`crate::keys::account_key`] of each address, with RLP-encoded
+/// `[nonce, balance, storage_root, code_hash]` values.
+///
+/// # Arguments
+/// * `accounts` - Accounts to insert into the state trie, with their storage
+///
+/// # Returns
+/// * State root and RLP-encoded proof nodes of the account trie and of each storage trie
+///
+/// # Panics
+/// * If `accounts` contain duplicate addresses, or an account contains duplicate slots
+pub fn build_account_with_storage(
+  accounts: &[(alloy_primitives::Address, AccountWithStorage)],
+) -> StateTrieProof {
+  let storage = accounts
+    .iter()
+    .map(|(address, account)| {
+      let (storage_root, nodes) = storage_trie(&account.storage);
+      StorageTrieProof {
+        address: *address,
+        storage_root,
+        nodes,
+      }
+    })
+    .collect::<Vec<_>>();
+
+  let account_items = accounts
+    .iter()
+    .zip(&storage)
+    .map(|((address, account), storage)| {
+      let key = crate::keys::account_key(*address);
+      let account = Account {
+        nonce: account.nonce,
+        balance: account.balance,
+        storage_root: storage.storage_root,
+        code_hash: account.code_hash,
+      };
+      (key, encode_account(&account))
+    })
+    .collect::<Vec<_>>();
+  let (state_root, account_nodes) = crate::build_alloy_trie_with_proof(&account_items);
+
+  StateTrieProof {
+    state_root,
+    account_nodes,
+    storage,
+  }
+}
+
+/// Builds an Ethereum storage trie of given slots
+///
+/// Each slot is keyed by `keccak256` of its 32-byte form, and holds the RLP of its value - a
+/// big-endian integer with leading zeros stripped, as Geth and Reth store it. Slots holding zero
+/// are absent from the storage trie, so they are skipped.
+///
+/// # Arguments
+/// * `slots` - Storage slots and their values
+///
+/// # Returns
+/// * Storage root and RLP-encoded proof nodes, or the empty root and no nodes if all values are
+///   zero
+///
+/// # Panics
+/// * If `slots` contain duplicate slots
+pub fn build_storage_trie(
+  slots: &[(alloy_primitives::B256, alloy_primitives::U256)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  crate::build_alloy_trie_with_proof(&storage_items(slots))
+}
+
+/// Computes the storage root of a contract with given slots
+///
+/// Same as [`build_storage_trie`], but no proof nodes are retained.
+///
+/// # Arguments
+/// * `slots` - Storage slots and their values
+///
+/// # Returns
+/// * Storage root, or the empty root if all values are zero
+///
+/// # Panics
+/// * If `slots` contain duplicate slots
+pub fn storage_root(
+  slots: &[(alloy_primitives::B256, alloy_primitives::U256)],
+) -> alloy_primitives::B256 {
+  crate::alloy_root_only(&storage_items(slots))
+}
+
+/// Keys non-zero slots by their hash, and RLP-encodes their values.
+fn storage_items(
+  slots: &[(alloy_primitives::B256, alloy_primitives::U256)],
+) -> Vec<(alloy_primitives::B256, Vec<u8>)> {
+  slots
+    .iter()
+    .filter(|(_, value)| !value.is_zero())
+    .map(|(slot, value)| (alloy_primitives::keccak256(slot), alloy_rlp::encode(value)))
+    .collect()
+}
+
+/// Builds the storage trie of given slots, skipping the ones holding zero.
+fn storage_trie(
+  storage: &[(alloy_primitives::U256, alloy_primitives::U256)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let slots = storage
+    .iter()
+    .map(|(slot, value)| (alloy_primitives::B256::from(*slot), *value))
+    .collect::<Vec<_>>();
+  build_storage_trie(&slots)
+}
+
+/// Encodes the account leaf value, as `[nonce, balance, storage_root, code_hash]` list.
+fn encode_account(account: &Account) -> Vec<u8> {
+  let payload_length = account.nonce.length()
+    + account.balance.length()
+    + account.storage_root.length()
+    + account.code_hash.length();
+
+  let mut out = Vec::new();
+  alloy_rlp::Header {
+    list: true,
+    payload_length,
+  }
+  .encode(&mut out);
+  account.nonce.encode(&mut out);
+  account.balance.encode(&mut out);
+  account.storage_root.encode(&mut out);
+  account.code_hash.encode(&mut out);
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  /// Hand-encodes the root of a trie of hashed slots all differing in the first nibble - a branch
+  /// with a leaf per slot, each referenced by hash
+  ///
+  /// # Arguments
+  /// * `leaves` - Hashed slots, and values already encoded as the RLP string stored in the leaf
+  fn hand_encoded_root_branch(leaves: &[(alloy_primitives::B256, &[u8])]) -> Vec<u8> {
+    let mut slots = vec![vec![alloy_rlp::EMPTY_STRING_CODE]; 16];
+    for (hashed_slot, value) in leaves {
+      // Leaf key is the odd-length remainder of the path, so the first nibble shares the flag byte.
+      let mut payload = vec![0xA0, 0x30 | (hashed_slot[0] & 0x0F)];
+      payload.extend(&hashed_slot[1..]);
+      payload.extend(*value);
+      let mut leaf = vec![0xC0 + payload.len() as u8];
+      leaf.extend(payload);
+
+      let mut reference = vec![0xA0];
+      reference.extend(alloy_primitives::keccak256(&leaf).as_slice());
+      slots[usize::from(hashed_slot[0] >> 4)] = reference;
+    }
+
+    let mut payload = slots.concat();
+    payload.push(alloy_rlp::EMPTY_STRING_CODE);
+    let mut branch = vec![0xF8, payload.len() as u8];
+    branch.extend(payload);
+    branch
+  }
+
+  #[test]
+  fn test_account_with_two_storage_slots() {
+    let address = alloy_primitives::Address::repeat_byte(0x11);
+    let storage = vec![
+      (
+        alloy_primitives::U256::from(0),
+        alloy_primitives::U256::from(42),
+      ),
+      (
+        alloy_primitives::U256::from(1),
+        alloy_primitives::U256::from(0xdead_beef_u64),
+      ),
+    ];
+    let account = super::AccountWithStorage {
+      nonce: 1,
+      balance: alloy_primitives::U256::from(1_000_000),
+      code_hash: alloy_primitives::KECCAK256_EMPTY,
+      storage: storage.clone(),
+    };
+    let state = super::build_account_with_storage(&[(address, account.clone())]);
+
+    // Storage slots are proven against the storage root.
+    let storage_trie = &state.storage[0];
+    assert_eq!(storage_trie.address, address);
+    for (slot, value) in &storage {
+      let key = crate::keys::storage_key(*slot);
+      assert_eq!(
+        crate::verify_key_path(
+          storage_trie.storage_root,
+          &storage_trie.nodes,
+          key.as_slice()
+        ),
+        Ok(Some(alloy_rlp::encode(value)))
+      );
+    }
+
+    // Account leaf commits to the storage root.
+    let account_key = crate::keys::account_key(address);
+    let account_rlp = super::encode_account(&super::Account {
+      nonce: account.nonce,
+      balance: account.balance,
+      storage_root: storage_trie.storage_root,
+      code_hash: account.code_hash,
+    });
+    assert_eq!(
+      crate::verify_key_path(
+        state.state_root,
+        &state.account_nodes,
+        account_key.as_slice()
+      ),
+      Ok(Some(account_rlp))
+    );
+
+    let r0_trie = risc0_ethereum_trie::Trie::from_rlp(&state.account_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), state.state_root);
+  }
+
+  #[test]
+  fn test_account_without_storage_has_empty_storage_root() {
+    let account = super::AccountWithStorage {
+      nonce: 0,
+      balance: alloy_primitives::U256::from(1),
+      code_hash: alloy_primitives::KECCAK256_EMPTY,
+      storage: vec![(
+        alloy_primitives::U256::from(7),
+        alloy_primitives::U256::ZERO,
+      )],
+    };
+    let state = super::build_account_with_storage(&[(alloy_primitives::Address::ZERO, account)]);
+    assert_eq!(state.storage[0].storage_root, crate::EMPTY_ROOT);
+    assert!(state.storage[0].nodes.is_empty());
+  }
+
+  #[test]
+  fn test_storage_trie_trims_values() {
+    let slots = [
+      (
+        alloy_primitives::B256::with_last_byte(0),
+        alloy_primitives::U256::from(0x0100),
+      ),
+      (
+        alloy_primitives::B256::with_last_byte(1),
+        alloy_primitives::U256::from(1),
+      ),
+      (
+        alloy_primitives::B256::with_last_byte(2),
+        alloy_primitives::U256::ZERO,
+      ),
+    ];
+    let (storage_root, nodes) = super::build_storage_trie(&slots);
+    // Slots 0 and 1 hash to `0x290decd9..` and `0xb10e2d52..`, so the trie is a root branch with
+    // leaves in slots `2` and `B`. Values `0x0100` and `1` are RLP-encoded as `0x820100` and
+    // `0x01`, and then wrapped as strings in the leaves.
+    let root_node = hand_encoded_root_branch(&[
+      (
+        alloy_primitives::keccak256(slots[0].0),
+        &[0x83, 0x82, 0x01, 0x00][..],
+      ),
+      (alloy_primitives::keccak256(slots[1].0), &[0x01][..]),
+    ]);
+    let expected =
+      alloy_primitives::b256!("0x3d0f12c6c1e5acd2a5b5ce94b65fa82f712e54d34df386feb474b27025203e3b");
+    assert_eq!(alloy_primitives::keccak256(&root_node), expected);
+    assert_eq!(storage_root, expected);
+    assert_eq!(nodes[0].as_ref(), root_node.as_slice());
+
+    // Values are minimal big-endian integers, and the zero slot is absent.
+    let values = crate::iter_leaves(&nodes)
+      .unwrap()
+      .map(|(_, value)| value)
+      .collect::<Vec<_>>();
+    assert_eq!(values, vec![vec![0x82, 0x01, 0x00], vec![0x01]]);
+  }
+
+  #[test]
+  fn test_storage_root_of_token_layout() {
+    // Owner, total supply of 1M tokens with 18 decimals, and decimals.
+    let owner = alloy_primitives::address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+    let slots = [
+      (
+        alloy_primitives::B256::with_last_byte(0),
+        alloy_primitives::U256::from_be_slice(owner.as_slice()),
+      ),
+      (
+        alloy_primitives::B256::with_last_byte(1),
+        alloy_primitives::U256::from(10).pow(alloy_primitives::U256::from(24)),
+      ),
+      (
+        alloy_primitives::B256::with_last_byte(2),
+        alloy_primitives::U256::from(18),
+      ),
+    ];
+    let storage_root = super::storage_root(&slots);
+    // Slot 2 hashes to `0x405787fa..`, next to slots 0 and 1 in branch slots `2` and `B`. Each
+    // value is its minimal big-endian RLP - a 20-byte owner, a 10-byte supply and a single byte -
+    // wrapped once more as the leaf string.
+    let mut owner_value = vec![0x95, 0x94];
+    owner_value.extend(owner.as_slice());
+    let supply_value = [
+      0x8B, 0x8A, 0xD3, 0xC2, 0x1B, 0xCE, 0xCC, 0xED, 0xA1, 0x00, 0x00, 0x00,
+    ];
+    let root_node = hand_encoded_root_branch(&[
+      (alloy_primitives::keccak256(slots[0].0), &owner_value[..]),
+      (alloy_primitives::keccak256(slots[1].0), &supply_value[..]),
+      (alloy_primitives::keccak256(slots[2].0), &[0x12][..]),
+    ]);
+    let expected =
+      alloy_primitives::b256!("0xf904ee72486356ce3b5d6870365ee395fd17e274c6fd6eb6297cec8cd1e988af");
+    assert_eq!(alloy_primitives::keccak256(&root_node), expected);
+    assert_eq!(storage_root, expected);
+    assert_eq!(storage_root, super::build_storage_trie(&slots).0);
+    assert_eq!(super::storage_root(&[]), crate::EMPTY_ROOT);
+  }
+
+  #[test]
+  fn test_account_trie_builder_single_account() {
+    let address = alloy_primitives::address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+    let account = super::Account {
+      nonce: 1,
+      balance: alloy_primitives::U256::from(1_000_000_000_000_000_000u64),
+      storage_root: crate::EMPTY_ROOT,
+      code_hash: alloy_primitives::KECCAK256_EMPTY,
+    };
+    let mut builder = super::AccountTrieBuilder::new();
+    builder.add(address, account);
+    let (state_root, nodes) = builder.finish();
+    // Lone root leaf, keyed by the even-length path of the whole hashed address. Its value is the
+    // 78-byte account list `[nonce, balance, storage_root, code_hash]`, with the balance of 1 ether
+    // encoded in 8 bytes.
+    let mut account_rlp = vec![0xF8, 0x4C, 0x01, 0x88];
+    account_rlp.extend(1_000_000_000_000_000_000u64.to_be_bytes());
+    account_rlp.push(0xA0);
+    account_rlp.extend(crate::EMPTY_ROOT.as_slice());
+    account_rlp.push(0xA0);
+    account_rlp.extend(alloy_primitives::KECCAK256_EMPTY.as_slice());
+    let mut leaf = vec![0xF8, 0x72, 0xA1, 0x20];
+    leaf.extend(alloy_primitives::keccak256(address).as_slice());
+    leaf.extend([0xB8, 0x4E]);
+    leaf.extend(account_rlp);
+    let expected =
+      alloy_primitives::b256!("0xdb3ff0ebe04cf526bfe08b784268a8d2e477bb3eebfa882eae69a88c2a838843");
+    assert_eq!(alloy_primitives::keccak256(&leaf), expected);
+    assert_eq!(state_root, expected);
+    assert_eq!(nodes, vec![alloy_primitives::Bytes::from(leaf)]);
+
+    // Same leaf as for an account without storage built with its storage.
+    let state = super::build_account_with_storage(&[(
+      address,
+      super::AccountWithStorage {
+        nonce: account.nonce,
+        balance: account.balance,
+        code_hash: account.code_hash,
+        storage: vec![],
+      },
+    )]);
+    assert_eq!((state.state_root, state.account_nodes), (state_root, nodes));
+  }
+}
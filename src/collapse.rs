@@ -0,0 +1,1196 @@
+use alloc::vec::Vec;
+
+/// Errors returned when keys cannot be removed from a Risc0 trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollapseError {
+  /// Proof nodes could not be decoded into a Risc0 trie.
+  Rlp(alloy_rlp::Error),
+  /// The key to remove is not present in the trie.
+  KeyNotFound(alloy_primitives::Bytes),
+  /// Proof nodes do not prove the path of the key to remove.
+  InvalidProof(crate::ProofError),
+  /// Sibling to be pulled up by the collapse is not among the proof nodes.
+  MissingSibling,
+}
+
+impl core::fmt::Display for CollapseError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Rlp(err) => write!(f, "invalid proof nodes: {err}"),
+      Self::KeyNotFound(key) => write!(f, "key {key} not found in trie"),
+      Self::InvalidProof(err) => write!(f, "invalid proof of removed key: {err}"),
+      Self::MissingSibling => write!(f, "sibling to pull up is missing from proof nodes"),
+    }
+  }
+}
+
+impl core::error::Error for CollapseError {
+  fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+    match self {
+      Self::Rlp(err) => Some(err),
+      Self::InvalidProof(err) => Some(err),
+      Self::KeyNotFound(_) | Self::MissingSibling => None,
+    }
+  }
+}
+
+/// Stage of the collapse consistency check at which Alloy and Risc0 roots were compared.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyStage {
+  /// Trie built without the removee key.
+  Before,
+  /// Trie built with the removee key.
+  After,
+  /// Trie built with the removee key, which was then removed from the Risc0 trie.
+  AfterRemoval,
+}
+
+/// Errors returned when Risc0 trie disagrees with Alloy trie.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsistencyError {
+  /// Proof nodes could not be decoded into a Risc0 trie.
+  Rlp(alloy_rlp::Error),
+  /// Risc0 trie did not find the removee key to remove.
+  RemoveeNotFound(alloy_primitives::Bytes),
+  /// Roots differ at given stage.
+  Mismatch {
+    stage: ConsistencyStage,
+    alloy: alloy_primitives::B256,
+    risc0: alloy_primitives::B256,
+  },
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for ConsistencyError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Rlp(err) => write!(f, "invalid proof nodes: {err}"),
+      Self::RemoveeNotFound(key) => write!(f, "removee key {key} not found in Risc0 trie"),
+      Self::Mismatch {
+        stage,
+        alloy,
+        risc0,
+      } => write!(
+        f,
+        "root mismatch at stage {stage:?}: Alloy {alloy}, Risc0 {risc0}"
+      ),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for ConsistencyError {
+  fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+    match self {
+      Self::Rlp(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+/// Validates Risc0 trie implementation against Alloy trie (reference implementation)
+///
+/// Checks three stages:
+/// 1. Build trie without removee key - verify Risc0 matches Alloy hash (baseline)
+/// 2. Build trie with removee key - verify Risc0 matches Alloy hash (extended form)
+/// 3. Dynamically remove the removee key from Risc0 trie and verify it matches baseline
+///
+/// # Arguments
+/// * `keys` - Key-value pairs forming the baseline trie
+/// * `removee_key` - Key to add and then remove, must not be part of `keys`
+///
+/// # Returns
+/// * `Ok(())` if all stages agree, or the first stage whose roots differ
+#[cfg(feature = "std")]
+pub fn assert_collapse_consistency<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  keys: &[(K, V)],
+  removee_key: &K,
+) -> Result<(), ConsistencyError> {
+  check_multi_collapse_consistency(keys, &[(removee_key.as_ref(), b"removee".as_slice())])
+}
+
+/// Validates Risc0 trie implementation against Alloy trie, removing several keys at once
+///
+/// Same as [`assert_collapse_consistency`], but all `extra_keys` are added and then removed one
+/// by one, which may trigger multiple (cascading) collapses.
+///
+/// # Arguments
+/// * `base_keys` - Key-value pairs forming the baseline trie
+/// * `extra_keys` - Key-value pairs to add and then remove, must not overlap with `base_keys`
+///
+/// # Returns
+/// * `Ok(())` if all stages agree, or the first stage whose roots differ
+#[cfg(feature = "std")]
+pub fn check_multi_collapse_consistency<K1, V1, K2, V2>(
+  base_keys: &[(K1, V1)],
+  extra_keys: &[(K2, V2)],
+) -> Result<(), ConsistencyError>
+where
+  K1: AsRef<[u8]> + Ord,
+  V1: AsRef<[u8]>,
+  K2: AsRef<[u8]> + Ord,
+  V2: AsRef<[u8]>,
+{
+  let base_keys = base_keys
+    .iter()
+    .map(|(k, v)| (k.as_ref(), v.as_ref()))
+    .collect::<Vec<_>>();
+  let mut all_keys = base_keys.clone();
+  all_keys.extend(extra_keys.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  // Build trie without extra keys.
+  let (alloy_hash_before, rlp_nodes_before) = crate::build_alloy_trie_with_proof(&base_keys);
+  let r0_trie_before =
+    risc0_ethereum_trie::Trie::from_rlp(rlp_nodes_before).map_err(ConsistencyError::Rlp)?;
+  check_stage(
+    ConsistencyStage::Before,
+    alloy_hash_before,
+    r0_trie_before.hash_slow(),
+  )?;
+
+  // Build trie with extra keys.
+  let (alloy_hash_after, rlp_nodes_after) = crate::build_alloy_trie_with_proof(&all_keys);
+  let mut r0_trie =
+    risc0_ethereum_trie::Trie::from_rlp(rlp_nodes_after).map_err(ConsistencyError::Rlp)?;
+  check_stage(
+    ConsistencyStage::After,
+    alloy_hash_after,
+    r0_trie.hash_slow(),
+  )?;
+
+  // Get rid of extra keys from the latter Risc0 trie and compare.
+  for (key, _) in extra_keys {
+    if !r0_trie.remove(key) {
+      return Err(ConsistencyError::RemoveeNotFound(
+        alloy_primitives::Bytes::copy_from_slice(key.as_ref()),
+      ));
+    }
+  }
+  check_stage(
+    ConsistencyStage::AfterRemoval,
+    alloy_hash_before,
+    r0_trie.hash_slow(),
+  )
+}
+
+/// Compares roots computed by Alloy and Risc0 at given stage.
+#[cfg(feature = "std")]
+fn check_stage(
+  stage: ConsistencyStage,
+  alloy: alloy_primitives::B256,
+  risc0: alloy_primitives::B256,
+) -> Result<(), ConsistencyError> {
+  if alloy != risc0 {
+    return Err(ConsistencyError::Mismatch {
+      stage,
+      alloy,
+      risc0,
+    });
+  }
+  Ok(())
+}
+
+/// Summary of a single removal from a Risc0 trie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemovalStats {
+  /// Whether the key was present, and therefore removed.
+  pub existed: bool,
+  /// Number of trie nodes before the removal.
+  pub size_before: usize,
+  /// Number of trie nodes after the removal.
+  pub size_after: usize,
+}
+
+impl RemovalStats {
+  /// Change in the number of trie nodes, negative if nodes were collapsed.
+  pub fn node_delta(&self) -> isize {
+    self.size_after as isize - self.size_before as isize
+  }
+}
+
+/// Trie built with Alloy and converted to Risc0 representation, ready for removal experiments
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct CollapsibleTrie {
+  /// Root hash computed by Alloy.
+  root: alloy_primitives::B256,
+  /// RLP-encoded nodes of the Alloy trie, root node first.
+  rlp_nodes: Vec<alloy_primitives::Bytes>,
+  /// Risc0 trie reconstructed from `rlp_nodes`, modified by removals.
+  trie: risc0_ethereum_trie::Trie,
+}
+
+#[cfg(feature = "std")]
+impl CollapsibleTrie {
+  /// Builds an Alloy trie with proofs for all items and converts it to a Risc0 trie
+  ///
+  /// # Arguments
+  /// * `items` - Key-value pairs to insert into the trie
+  pub fn from_items<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+    items: &[(K, V)],
+  ) -> Result<Self, CollapseError> {
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(items);
+    let trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).map_err(CollapseError::Rlp)?;
+    Ok(Self {
+      root,
+      rlp_nodes,
+      trie,
+    })
+  }
+
+  /// Root hash of the trie as originally built by Alloy.
+  pub fn root(&self) -> alloy_primitives::B256 {
+    self.root
+  }
+
+  /// RLP-encoded nodes of the trie as originally built by Alloy.
+  pub fn rlp_nodes(&self) -> &[alloy_primitives::Bytes] {
+    &self.rlp_nodes
+  }
+
+  /// Risc0 trie in its current state.
+  pub fn trie(&self) -> &risc0_ethereum_trie::Trie {
+    &self.trie
+  }
+
+  /// Removes a key from the Risc0 trie, returning whether it was present.
+  pub fn remove(&mut self, key: impl AsRef<[u8]>) -> bool {
+    self.trie.remove(key)
+  }
+
+  /// Removes a key from the Risc0 trie, reporting how the trie size changed.
+  pub fn remove_with_stats(&mut self, key: impl AsRef<[u8]>) -> RemovalStats {
+    let size_before = self.trie.size();
+    let existed = self.trie.remove(key);
+    let size_after = self.trie.size();
+    RemovalStats {
+      existed,
+      size_before,
+      size_after,
+    }
+  }
+
+  /// Removes every key whose path starts with `prefix`, returning the number of keys removed
+  ///
+  /// Risc0 trie cannot enumerate its keys, so candidates are taken from the original Alloy nodes
+  /// and removed one by one - each removal collapses the trie, so once the whole subtree is gone,
+  /// its remaining sibling is pulled up as usual. Keys removed earlier are not counted again.
+  ///
+  /// # Arguments
+  /// * `prefix` - Nibble path shared by all keys to remove
+  ///
+  /// # Returns
+  /// * Number of keys removed
+  pub fn remove_prefix(&mut self, prefix: &alloy_trie::Nibbles) -> usize {
+    let leaves =
+      crate::nodes::collect_leaves(&self.rlp_nodes).expect("nodes built by Alloy are well-formed");
+
+    let mut removed = 0;
+    for (path, _) in leaves {
+      if path.starts_with(prefix) && self.trie.remove(path.pack()) {
+        removed += 1;
+      }
+    }
+    removed
+  }
+
+  /// Root hash of the Risc0 trie after all removals so far.
+  pub fn collapsed_root(&self) -> alloy_primitives::B256 {
+    self.trie.hash_slow()
+  }
+}
+
+/// Removes a key from a Risc0 trie, returning roots before and after the removal
+///
+/// # Arguments
+/// * `trie` - Risc0 trie to remove from
+/// * `key` - Key to remove
+///
+/// # Returns
+/// * Root hashes before and after the removal, or [`CollapseError::KeyNotFound`] if the key is
+///   absent - in which case the trie is left unchanged
+pub fn remove_with_root_transition(
+  trie: &mut risc0_ethereum_trie::Trie,
+  key: &alloy_primitives::B256,
+) -> Result<(alloy_primitives::B256, alloy_primitives::B256), CollapseError> {
+  let root_before = trie.hash_slow();
+  if !trie.remove(key) {
+    return Err(CollapseError::KeyNotFound(
+      alloy_primitives::Bytes::copy_from_slice(key.as_slice()),
+    ));
+  }
+  Ok((root_before, trie.hash_slow()))
+}
+
+/// Removes a key from a partial trie given only by its proof, returning the new root
+///
+/// Proof nodes must cover the path of the key, and the sibling pulled up if the removal leaves
+/// its parent branch with a single child - e.g. as returned by [`crate::removal_witness`]. Both
+/// are checked upfront, as Risc0 trie cannot collapse into an unresolved node.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie before the removal
+/// * `rlp_nodes` - RLP-encoded proof nodes, root node first
+/// * `key` - Key to remove
+///
+/// # Returns
+/// * Root hash after the removal, or an error if the proof is invalid, the key is absent or the
+///   sibling is missing
+pub fn remove_from_proof(
+  root: alloy_primitives::B256,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<alloy_primitives::B256, CollapseError> {
+  match crate::verify_key_path(root, rlp_nodes, key.as_slice()) {
+    Ok(Some(_)) => {}
+    Ok(None) => {
+      return Err(CollapseError::KeyNotFound(
+        alloy_primitives::Bytes::copy_from_slice(key.as_slice()),
+      ));
+    }
+    Err(err) => return Err(CollapseError::InvalidProof(err)),
+  }
+  // With the path resolved, only the sibling can prevent the collapse.
+  if !can_collapse(rlp_nodes, key) {
+    return Err(CollapseError::MissingSibling);
+  }
+
+  let mut trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).map_err(CollapseError::Rlp)?;
+  trie.remove(key);
+  Ok(trie.hash_slow())
+}
+
+/// Removes a key from a Risc0 trie, returning hashes of the nodes freed by the removal
+///
+/// Risc0 trie does not expose its nodes, so the trie after removal is rebuilt with Alloy from the
+/// remaining leaves of `rlp_nodes`, and checked to have the same root. Freed are the nodes stored
+/// by hash before the removal, but not after - so they can be pruned from a backing node store.
+/// Nodes inlined in their parent are never stored on their own, so they are not reported.
+///
+/// The rebuild hashes every node of the trie, so each call costs time linear in the number of
+/// leaves - not just the nodes on the path of `key`, as the removal itself does.
+///
+/// # Arguments
+/// * `trie` - Risc0 trie to remove from, changed only if the call succeeds
+/// * `rlp_nodes` - RLP-encoded nodes of the whole current `trie`, root node first
+/// * `key` - Key to remove
+///
+/// # Returns
+/// * Hashes of freed nodes, in the order of `rlp_nodes` - empty if the key is absent - or an error
+///   if `rlp_nodes` do not hash to the root of `trie`, are incomplete, or the roots after removal
+///   disagree
+#[cfg(feature = "std")]
+pub fn remove_returning_freed(
+  trie: &mut risc0_ethereum_trie::Trie,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<Vec<alloy_primitives::B256>, crate::ProofError> {
+  check_nodes_root(trie, rlp_nodes)?;
+  // Removal is done on a copy, so that `trie` is left as it was if the roots disagree.
+  let mut trie_after = trie.clone();
+  if !trie_after.remove(key) {
+    return Ok(Vec::new());
+  }
+  let (_, nodes_after) = rebuild_after_removal(&trie_after, rlp_nodes, key)?;
+  *trie = trie_after;
+
+  let stored_after =
+    stored_node_hashes(&nodes_after).collect::<alloy_primitives::map::HashSet<_>>();
+  Ok(
+    stored_node_hashes(rlp_nodes)
+      .filter(|hash| !stored_after.contains(hash))
+      .collect(),
+  )
+}
+
+/// Rebuilds the nodes of a Risc0 trie after removing a key, from the remaining leaves of the nodes
+/// before the removal
+///
+/// # Returns
+/// * Root and nodes of the trie after removal, or an error if `rlp_nodes` are incomplete or the
+///   rebuilt root differs from the root of `trie_after`
+#[cfg(feature = "std")]
+fn rebuild_after_removal(
+  trie_after: &risc0_ethereum_trie::Trie,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<(alloy_primitives::B256, Vec<alloy_primitives::Bytes>), crate::ProofError> {
+  let path = alloy_trie::Nibbles::unpack(key);
+  let remaining_leaves = crate::nodes::collect_leaves(rlp_nodes)?
+    .into_iter()
+    .filter(|(leaf_path, _)| *leaf_path != path)
+    .collect::<Vec<_>>();
+  let proof_key_paths = remaining_leaves.iter().map(|(path, _)| *path).collect();
+  let (expected, nodes_after) =
+    crate::build_alloy_trie_from_sorted(remaining_leaves, proof_key_paths);
+
+  let actual = trie_after.hash_slow();
+  if actual != expected {
+    return Err(crate::ProofError::RootMismatch { expected, actual });
+  }
+  Ok((expected, nodes_after))
+}
+
+/// Outcome of [`remove_or_prove_absent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemovalOutcome {
+  /// Key was present, with the root hash and the nodes of the trie after removal.
+  Removed {
+    /// Root hash of the trie after removal.
+    root: alloy_primitives::B256,
+    /// RLP-encoded nodes of the trie after removal, root node first.
+    nodes: Vec<alloy_primitives::Bytes>,
+  },
+  /// Key was absent and the trie is unchanged, with the nodes on its path proving the absence.
+  Absent(Vec<alloy_primitives::Bytes>),
+}
+
+/// Removes a key from a Risc0 trie, or proves it absent if there is nothing to remove
+///
+/// Risc0 trie does not expose its nodes, so the exclusion proof is taken from the RLP nodes the
+/// trie is built from - checked to hash to its root first, so the proof verifies against the
+/// unchanged root. After a removal, the nodes of the changed trie are rebuilt with Alloy from the
+/// remaining leaves and returned, to be passed to the next call. The rebuild hashes every node of
+/// the trie, so a removal costs time linear in the number of leaves.
+///
+/// # Arguments
+/// * `trie` - Risc0 trie to remove from, changed only if the call succeeds
+/// * `rlp_nodes` - RLP-encoded nodes of the whole current `trie`, root node first
+/// * `key` - Key to remove
+///
+/// # Returns
+/// * Root and nodes after removal, or the exclusion proof of `key` - or an error if `rlp_nodes` do
+///   not hash to the root of `trie`, or are incomplete
+#[cfg(feature = "std")]
+pub fn remove_or_prove_absent(
+  trie: &mut risc0_ethereum_trie::Trie,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<RemovalOutcome, crate::ProofError> {
+  check_nodes_root(trie, rlp_nodes)?;
+  // Removal is done on a copy, so that `trie` is left as it was if the nodes are incomplete.
+  let mut trie_after = trie.clone();
+  if trie_after.remove(key) {
+    let (root, nodes) = rebuild_after_removal(&trie_after, rlp_nodes, key)?;
+    *trie = trie_after;
+    return Ok(RemovalOutcome::Removed { root, nodes });
+  }
+  let exclusion_proof = crate::nodes::path_nodes(rlp_nodes, &alloy_trie::Nibbles::unpack(key))?;
+  Ok(RemovalOutcome::Absent(exclusion_proof))
+}
+
+/// Checks that RLP nodes hash to the root of a Risc0 trie, i.e. are the nodes of its current state.
+#[cfg(feature = "std")]
+fn check_nodes_root(
+  trie: &risc0_ethereum_trie::Trie,
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<(), crate::ProofError> {
+  let expected = rlp_nodes
+    .first()
+    .map_or(alloy_trie::EMPTY_ROOT_HASH, alloy_primitives::keccak256);
+  let actual = trie.hash_slow();
+  if actual != expected {
+    return Err(crate::ProofError::RootMismatch { expected, actual });
+  }
+  Ok(())
+}
+
+/// Hashes of nodes referenced by hash - the root, and all nodes of at least 32 bytes.
+#[cfg(feature = "std")]
+fn stored_node_hashes(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> impl Iterator<Item = alloy_primitives::B256> + '_ {
+  rlp_nodes
+    .iter()
+    .enumerate()
+    .filter(|(i, rlp)| *i == 0 || rlp.len() >= 32)
+    .map(|(_, rlp)| alloy_primitives::keccak256(rlp))
+}
+
+/// Removes keys from a Risc0 trie and returns the root of the collapsed trie
+///
+/// The trie is built with Alloy from all `keys`, converted to Risc0 representation, and then
+/// each of `remove` is dynamically removed from it.
+///
+/// # Arguments
+/// * `keys` - Key-value pairs forming the initial trie, including the keys to remove
+/// * `remove` - Keys to remove, in order of removal
+///
+/// # Returns
+/// * Root hash of the collapsed trie, which is the empty root if all keys were removed
+#[cfg(feature = "std")]
+pub fn collapse_after_removal<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  keys: &[(K, V)],
+  remove: &[K],
+) -> Result<alloy_primitives::B256, CollapseError> {
+  let mut trie = CollapsibleTrie::from_items(keys)?;
+
+  for key in remove {
+    if !trie.remove(key) {
+      return Err(CollapseError::KeyNotFound(
+        alloy_primitives::Bytes::copy_from_slice(key.as_ref()),
+      ));
+    }
+  }
+
+  Ok(trie.collapsed_root())
+}
+
+/// Checks that removing a key from a (possibly partial) trie can be collapsed
+///
+/// When the removal leaves a branch with a single child, that child must be resolved to decide
+/// how the branch collapses - which is impossible if the proof only references it by hash. Risc0
+/// trie does not expose its nodes, so the check works on the RLP nodes it is built from.
+///
+/// **The answer refers to the trie encoded by `rlp_nodes`, not to a trie built from them and then
+/// changed.** After a removal or insertion the nodes are stale, and must be rebuilt from the
+/// remaining items (e.g. with [`crate::build_alloy_trie_with_proof`]) before checking the next
+/// removal.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the trie, root node first
+/// * `key` - Key to be removed
+///
+/// # Returns
+/// * `false` if a node on the path of `key`, or the sibling to be pulled up, is not among the
+///   nodes (or cannot be decoded), `true` otherwise - including when `key` is absent
+pub fn can_collapse(rlp_nodes: &[alloy_primitives::Bytes], key: &alloy_primitives::B256) -> bool {
+  find_orphan(rlp_nodes, key).is_ok()
+}
+
+/// Branch left with a single child by a removal.
+struct Orphan {
+  /// The only remaining child of the branch, to be pulled up by the collapse.
+  sibling: alloy_trie::nodes::TrieNode,
+  /// Whether the branch sits under an extension, which the collapse merges with.
+  parent_is_extension: bool,
+}
+
+/// Walks the path of `key` from the root, finding the deepest branch left with a single child by
+/// its removal, and resolving that child
+///
+/// # Returns
+/// * The orphaned branch, `None` if there is none or `key` is absent, or an error if a node on the
+///   path of `key`, or the sibling to be pulled up, is not among the nodes (or cannot be decoded)
+fn find_orphan(
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<Option<Orphan>, crate::nodes::NodeError> {
+  // Empty trie has nothing to remove.
+  let Some(root) = rlp_nodes.first() else {
+    return Ok(None);
+  };
+  let mut node = crate::nodes::decode(root)?;
+
+  let index = crate::nodes::NodeIndex::new(rlp_nodes);
+  let path = alloy_trie::Nibbles::unpack(key);
+  let mut depth = 0;
+  let mut parent_is_extension = false;
+  // Sibling of the path in the deepest branch left with a single child, and whether the parent of
+  // that branch is an extension.
+  let mut orphan = None;
+  loop {
+    let (child, is_extension) = match node {
+      alloy_trie::nodes::TrieNode::EmptyRoot => return Ok(None),
+      alloy_trie::nodes::TrieNode::Leaf(leaf) if path.slice(depth..) == leaf.key => break,
+      alloy_trie::nodes::TrieNode::Leaf(_) => return Ok(None),
+      alloy_trie::nodes::TrieNode::Extension(extension) => {
+        if !path.slice(depth..).starts_with(&extension.key) {
+          return Ok(None);
+        }
+        depth += extension.key.len();
+        (extension.child, true)
+      }
+      alloy_trie::nodes::TrieNode::Branch(branch) => {
+        let Some(nibble) = path.get(depth) else {
+          return Ok(None);
+        };
+        let Some(child) = crate::nodes::branch_child(&branch, nibble) else {
+          return Ok(None);
+        };
+        orphan = if branch.state_mask.get().count_ones() == 2 {
+          (0..16)
+            .find(|&sibling| sibling != nibble && branch.state_mask.is_bit_set(sibling))
+            .and_then(|sibling| crate::nodes::branch_child(&branch, sibling))
+            .map(|sibling| (sibling.clone(), parent_is_extension))
+        } else {
+          None
+        };
+        depth += 1;
+        (child.clone(), false)
+      }
+    };
+    parent_is_extension = is_extension;
+    node = index.resolve(&child)?;
+  }
+
+  // Leaf's parent is always a branch, so only the sibling in it may need to be pulled up.
+  orphan
+    .map(|(sibling, parent_is_extension)| {
+      Ok(Orphan {
+        sibling: index.resolve(&sibling)?,
+        parent_is_extension,
+      })
+    })
+    .transpose()
+}
+
+/// Kind of collapse triggered by a removal, see the README for the six underlying cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollapseKind {
+  /// No branch is left with a single child, e.g. the branch had more children or the key is absent.
+  None,
+  /// Orphaned branch under a branch turns into its only child, a leaf.
+  BranchToLeaf,
+  /// Orphaned branch under a branch turns into an extension, as its only child is a branch or
+  /// extension.
+  BranchToExtension,
+  /// Orphaned branch under an extension is collapsed, and merged with the parent extension.
+  ExtensionMerge,
+}
+
+/// Predicts the kind of collapse triggered by removing a key, without removing it
+///
+/// Risc0 trie does not expose its nodes, so the prediction works on the RLP nodes it is built
+/// from, finding the orphaned branch the same way as [`can_collapse`].
+///
+/// **The prediction refers to the trie encoded by `rlp_nodes`, not to a trie built from them and
+/// then changed.** After a removal or insertion the nodes are stale, and must be rebuilt from the
+/// remaining items (e.g. with [`crate::build_alloy_trie_with_proof`]) before predicting the next
+/// removal.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the trie, root node first
+/// * `key` - Key to be removed
+///
+/// # Returns
+/// * Kind of the collapse, or an error if a node on the path of `key`, or the sibling to be
+///   pulled up, is missing or cannot be decoded
+pub fn predict_collapse(
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &alloy_primitives::B256,
+) -> Result<CollapseKind, crate::ProofError> {
+  let Some(orphan) = find_orphan(rlp_nodes, key)? else {
+    return Ok(CollapseKind::None);
+  };
+  if orphan.parent_is_extension {
+    return Ok(CollapseKind::ExtensionMerge);
+  }
+  match orphan.sibling {
+    alloy_trie::nodes::TrieNode::Leaf(_) => Ok(CollapseKind::BranchToLeaf),
+    _ => Ok(CollapseKind::BranchToExtension),
+  }
+}
+
+/// Self-contained witness of removals from a trie, e.g. for consumption by a zkVM guest
+///
+/// The initial trie is given by `nodes`, its root being the hash of the first node. With the
+/// `serde` feature enabled, the witness serializes with hex strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollapseWitness {
+  /// Expected root hash of the trie after all removals.
+  pub root: alloy_primitives::B256,
+  /// RLP-encoded nodes of the initial trie, root node first.
+  pub nodes: Vec<alloy_primitives::Bytes>,
+  /// Keys to remove, in order of removal.
+  pub removals: Vec<alloy_primitives::B256>,
+}
+
+impl CollapseWitness {
+  /// Builds the witness for removing keys from the trie formed by given items
+  ///
+  /// Only the minimal nodes are included - for each removal, the nodes on the path of its key and
+  /// the sibling pulled up by the collapse (as in [`crate::removal_witness`]), found in the trie
+  /// left by the removals before it. Other nodes are referenced by hash only.
+  ///
+  /// # Arguments
+  /// * `items` - Key-value pairs forming the initial trie, including the keys to remove
+  /// * `removals` - Keys to remove, in order of removal
+  #[cfg(feature = "std")]
+  pub fn from_items<V: AsRef<[u8]>>(
+    items: &[(alloy_primitives::B256, V)],
+    removals: &[alloy_primitives::B256],
+  ) -> Result<Self, CollapseError> {
+    let mut trie = CollapsibleTrie::from_items(items)?;
+    for key in removals {
+      if !trie.remove(key) {
+        return Err(CollapseError::KeyNotFound(
+          alloy_primitives::Bytes::copy_from_slice(key.as_slice()),
+        ));
+      }
+    }
+
+    let sorted_items =
+      crate::sort_by_nibbles(items.iter().map(|(k, v)| (k.as_slice(), v.as_ref())));
+    let mut remaining_items = sorted_items.clone();
+    let mut proof_key_paths = Vec::new();
+    for key in removals {
+      let path = alloy_trie::Nibbles::unpack(key);
+      proof_key_paths.extend(crate::removal_proof_paths(&remaining_items, &path));
+      remaining_items.retain(|(item_path, _)| *item_path != path);
+    }
+    let (_, nodes) = crate::build_alloy_trie_from_sorted(sorted_items, proof_key_paths);
+
+    Ok(Self {
+      root: trie.collapsed_root(),
+      nodes,
+      removals: removals.to_vec(),
+    })
+  }
+
+  /// Reconstructs the trie, applies all removals and checks the collapsed root
+  ///
+  /// # Returns
+  /// * `true` if nodes decode, every removed key is present, and the collapsed root matches
+  pub fn verify(&self) -> bool {
+    let Ok(mut trie) = risc0_ethereum_trie::Trie::from_rlp(&self.nodes) else {
+      return false;
+    };
+    for key in &self.removals {
+      if !trie.remove(key) {
+        return false;
+      }
+    }
+    trie.hash_slow() == self.root
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::{create_test_data, key_from_nibbles};
+  use proptest::prelude::*;
+
+  #[test]
+  fn test_collapse_after_removal_matches_trie_without_removed_keys() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let remove = [key_from_nibbles("A0FF"), key_from_nibbles("E999")];
+    let collapsed_root = super::collapse_after_removal(&keys, &remove).unwrap();
+
+    let remaining_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2")]);
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(collapsed_root, expected_root);
+  }
+
+  #[test]
+  fn test_collapse_after_removal_reports_missing_key() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2")]);
+    let missing_key = key_from_nibbles("A0FF");
+    let result = super::collapse_after_removal(&keys, &[missing_key]);
+    assert_eq!(
+      result,
+      Err(super::CollapseError::KeyNotFound(
+        alloy_primitives::Bytes::copy_from_slice(missing_key.as_slice())
+      ))
+    );
+  }
+
+  #[test]
+  fn test_collapse_after_removal_of_all_keys_yields_empty_root() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let remove = keys.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+    let collapsed_root = super::collapse_after_removal(&keys, &remove).unwrap();
+    assert_eq!(collapsed_root, alloy_trie::EMPTY_ROOT_HASH);
+  }
+
+  #[test]
+  fn test_collapsible_trie_removal() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let mut trie = super::CollapsibleTrie::from_items(&keys).unwrap();
+    let (alloy_root, _) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(trie.root(), alloy_root);
+    assert_eq!(trie.collapsed_root(), alloy_root);
+
+    let removee_key = key_from_nibbles("A0FF");
+    assert!(trie.remove(removee_key));
+    assert!(!trie.remove(removee_key));
+
+    let remaining_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(trie.collapsed_root(), expected_root);
+    assert_eq!(trie.root(), alloy_root);
+  }
+
+  #[test]
+  fn test_remove_with_stats_reports_collapsed_nodes() {
+    // Case 1: removing the leaf collapses the orphaned branch into an extension.
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let mut trie = super::CollapsibleTrie::from_items(&keys).unwrap();
+
+    let stats = trie.remove_with_stats(key_from_nibbles("A0FF"));
+    assert!(stats.existed);
+    assert!(stats.node_delta() < 0);
+
+    let stats = trie.remove_with_stats(key_from_nibbles("A0FF"));
+    assert!(!stats.existed);
+    assert_eq!(stats.node_delta(), 0);
+  }
+
+  #[test]
+  fn test_multi_collapse_consistency_with_scattered_keys() {
+    let base_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let extra_keys = create_test_data(&[
+      ("A0FF", "4"),
+      ("A0F0", "5"),
+      ("ABC2", "6"),
+      ("E000", "7"),
+      ("7777", "8"),
+    ]);
+    assert_eq!(
+      super::check_multi_collapse_consistency(&base_keys, &extra_keys),
+      Ok(())
+    );
+  }
+
+  #[test]
+  fn test_remove_prefix_pulls_up_remaining_sibling() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let mut trie = super::CollapsibleTrie::from_items(&keys).unwrap();
+
+    // Whole subtree under `AB` disappears, leaving `A0FF` as the only child of branch `A`.
+    let prefix = alloy_trie::Nibbles::from_nibbles([0xA, 0xB]);
+    assert_eq!(trie.remove_prefix(&prefix), 2);
+    assert_eq!(trie.remove_prefix(&prefix), 0);
+
+    let remaining_keys = create_test_data(&[("A0FF", "4"), ("E999", "3")]);
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(trie.collapsed_root(), expected_root);
+
+    // Removing `A` leaves a single leaf in the trie.
+    let prefix = alloy_trie::Nibbles::from_nibbles([0xA]);
+    assert_eq!(trie.remove_prefix(&prefix), 1);
+
+    let remaining_keys = create_test_data(&[("E999", "3")]);
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(trie.collapsed_root(), expected_root);
+  }
+
+  #[test]
+  fn test_collapse_witness_verifies() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let removals = [key_from_nibbles("A0FF"), key_from_nibbles("E999")];
+    let witness = super::CollapseWitness::from_items(&keys, &removals).unwrap();
+
+    let remaining_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2")]);
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(witness.root, expected_root);
+    assert!(witness.verify());
+
+    // Root branch, branches `A` and `AB`, and leaves `A0FF` and `E999` - leaves under `AB` are
+    // referenced by hash only.
+    let (_, all_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(all_nodes.len(), 7);
+    assert_eq!(witness.nodes.len(), 5);
+
+    let mut wrong_root = witness.clone();
+    wrong_root.root = alloy_trie::EMPTY_ROOT_HASH;
+    assert!(!wrong_root.verify());
+
+    let mut missing_removal = witness.clone();
+    missing_removal.removals.push(key_from_nibbles("7777"));
+    assert!(!missing_removal.verify());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_collapse_witness_json_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4")]);
+    let witness = super::CollapseWitness::from_items(&keys, &[key_from_nibbles("A0FF")]).unwrap();
+
+    let json = serde_json::to_string(&witness).unwrap();
+    let decoded: super::CollapseWitness = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, witness);
+    assert!(decoded.verify());
+  }
+
+  #[test]
+  fn test_can_collapse_requires_resolved_sibling() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let removee_key = key_from_nibbles("A0FF");
+
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert!(super::can_collapse(&rlp_nodes, &removee_key));
+
+    // Proof of the removee alone references sibling branch `AB` by hash only.
+    let rlp_nodes = crate::build_alloy_trie_with_selective_proof(&keys, &[removee_key]).1;
+    assert!(!super::can_collapse(&rlp_nodes, &removee_key));
+
+    // Removing `E999` leaves the root branch with branch `A`, which is part of the proof.
+    let rlp_nodes =
+      crate::build_alloy_trie_with_selective_proof(&keys, &[key_from_nibbles("E999"), removee_key])
+        .1;
+    assert!(super::can_collapse(&rlp_nodes, &key_from_nibbles("E999")));
+
+    // Absent key is never removed.
+    assert!(super::can_collapse(&rlp_nodes, &key_from_nibbles("7777")));
+  }
+
+  #[test]
+  fn test_remove_with_root_transition_matches_alloy_builds() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let (root_before, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).unwrap();
+
+    let removee_key = key_from_nibbles("A0FF");
+    let transition = super::remove_with_root_transition(&mut r0_trie, &removee_key).unwrap();
+
+    let remaining_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let root_after = crate::alloy_root_only(&remaining_keys);
+    assert_eq!(transition, (root_before, root_after));
+
+    assert_eq!(
+      super::remove_with_root_transition(&mut r0_trie, &removee_key),
+      Err(super::CollapseError::KeyNotFound(
+        alloy_primitives::Bytes::copy_from_slice(removee_key.as_slice())
+      ))
+    );
+    assert_eq!(r0_trie.hash_slow(), root_after);
+  }
+
+  /// Random set of 1 to 50 distinct keys, with a random subset of them in random removal order.
+  fn keys_with_removals() -> impl Strategy<Value = (Vec<[u8; 32]>, Vec<[u8; 32]>)> {
+    prop::collection::btree_set(any::<[u8; 32]>(), 1..=50).prop_flat_map(|keys| {
+      let keys = keys.into_iter().collect::<Vec<_>>();
+      let removals = prop::sample::subsequence(keys.clone(), 0..=keys.len()).prop_shuffle();
+      (Just(keys), removals)
+    })
+  }
+
+  proptest! {
+    #[test]
+    fn test_removal_matches_rebuild_without_removed_keys(
+      (keys, removals) in keys_with_removals()
+    ) {
+      let items = keys
+        .iter()
+        .map(|key| (alloy_primitives::B256::from(*key), key.to_vec()))
+        .collect::<Vec<_>>();
+      let removals = removals
+        .into_iter()
+        .map(alloy_primitives::B256::from)
+        .collect::<Vec<_>>();
+      let remaining_items = items
+        .iter()
+        .filter(|(key, _)| !removals.contains(key))
+        .cloned()
+        .collect::<Vec<_>>();
+
+      let collapsed_root = super::collapse_after_removal(&items, &removals).unwrap();
+      prop_assert_eq!(collapsed_root, crate::alloy_root_only(&remaining_items));
+    }
+  }
+
+  #[test]
+  fn test_remove_from_proof_needs_sibling() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let removee_key = key_from_nibbles("A0FF");
+    let (old_root, new_root, nodes) = crate::removal_witness(&keys, &removee_key);
+    assert_eq!(
+      super::remove_from_proof(old_root, &nodes, &removee_key),
+      Ok(new_root)
+    );
+
+    // Branch `AB` is pulled up into branch `A`, but is referenced by hash only.
+    let sibling_hash = alloy_primitives::keccak256(&nodes[nodes.len() - 1]);
+    let trimmed_nodes = crate::branch_for_key(&keys, &removee_key);
+    assert!(
+      trimmed_nodes
+        .iter()
+        .all(|node| alloy_primitives::keccak256(node) != sibling_hash)
+    );
+    assert_eq!(
+      super::remove_from_proof(old_root, &trimmed_nodes, &removee_key),
+      Err(super::CollapseError::MissingSibling)
+    );
+
+    assert_eq!(
+      super::remove_from_proof(old_root, &nodes, &key_from_nibbles("ABE0")),
+      Err(super::CollapseError::KeyNotFound(
+        alloy_primitives::Bytes::copy_from_slice(key_from_nibbles("ABE0").as_slice())
+      ))
+    );
+  }
+
+  #[test]
+  fn test_predict_collapse_of_six_cases() {
+    let cases: [(&[(&str, &str)], super::CollapseKind); 6] = [
+      (
+        &[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")],
+        super::CollapseKind::BranchToExtension,
+      ),
+      (
+        &[
+          ("AB3C1", "1"),
+          ("AB3D2", "2"),
+          ("A0FFF", "3"),
+          ("E9999", "4"),
+        ],
+        super::CollapseKind::BranchToExtension,
+      ),
+      (
+        &[("AB1", "1"), ("A0F", "3"), ("E99", "4")],
+        super::CollapseKind::BranchToLeaf,
+      ),
+      (
+        &[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3")],
+        super::CollapseKind::ExtensionMerge,
+      ),
+      (
+        &[("AB3C1", "1"), ("AB3D2", "2"), ("A0FFF", "3")],
+        super::CollapseKind::ExtensionMerge,
+      ),
+      (
+        &[("AB1", "1"), ("A0F", "3")],
+        super::CollapseKind::ExtensionMerge,
+      ),
+    ];
+    for (keys, expected) in cases {
+      let keys = create_test_data(keys);
+      // Removee is the `A0..` key in each case.
+      let (removee_key, _) = *keys.iter().find(|(key, _)| key[0] == 0xA0).unwrap();
+      let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+      assert_eq!(
+        super::predict_collapse(&rlp_nodes, &removee_key),
+        Ok(expected)
+      );
+    }
+  }
+
+  #[test]
+  fn test_predict_collapse_without_orphaned_branch() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("A5FF", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    // Branch `A` keeps two children.
+    assert_eq!(
+      super::predict_collapse(&rlp_nodes, &key_from_nibbles("A0FF")),
+      Ok(super::CollapseKind::None)
+    );
+    assert_eq!(
+      super::predict_collapse(&rlp_nodes, &key_from_nibbles("E999")),
+      Ok(super::CollapseKind::None)
+    );
+  }
+
+  #[test]
+  fn test_predict_collapse_of_two_removals_in_a_row() {
+    let mut keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(
+      super::predict_collapse(&rlp_nodes, &key_from_nibbles("A0FF")),
+      Ok(super::CollapseKind::BranchToExtension)
+    );
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).unwrap();
+    assert!(r0_trie.remove(key_from_nibbles("A0FF")));
+
+    // Stale nodes still hold branch `A`, with branch `AB` under it.
+    assert_eq!(
+      super::predict_collapse(&rlp_nodes, &key_from_nibbles("ABD2")),
+      Ok(super::CollapseKind::BranchToLeaf)
+    );
+    // Rebuilt from the remaining items, branch `AB` is under the extension at `A`.
+    keys.retain(|(key, _)| *key != key_from_nibbles("A0FF"));
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(root, r0_trie.hash_slow());
+    assert_eq!(
+      super::predict_collapse(&rlp_nodes, &key_from_nibbles("ABD2")),
+      Ok(super::CollapseKind::ExtensionMerge)
+    );
+  }
+
+  #[test]
+  fn test_remove_returning_freed_reports_replaced_nodes() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root_before, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).unwrap();
+
+    let removee_key = key_from_nibbles("A0FF");
+    let freed = super::remove_returning_freed(&mut r0_trie, &rlp_nodes, &removee_key).unwrap();
+
+    let remaining_keys = keys
+      .iter()
+      .filter(|(key, _)| *key != removee_key)
+      .cloned()
+      .collect::<Vec<_>>();
+    let (root_after, rlp_nodes_after) = crate::build_alloy_trie_with_proof(&remaining_keys);
+    assert_eq!(r0_trie.hash_slow(), root_after);
+
+    let hashes_after = rlp_nodes_after
+      .iter()
+      .map(alloy_primitives::keccak256)
+      .collect::<Vec<_>>();
+    let expected = rlp_nodes
+      .iter()
+      .map(alloy_primitives::keccak256)
+      .filter(|hash| !hashes_after.contains(hash))
+      .collect::<Vec<_>>();
+    assert_eq!(freed, expected);
+    // Root branch, branch `A` (replaced by extension `A`) and the removed leaf.
+    assert_eq!(freed.len(), 3);
+
+    // Nodes of the trie before removal no longer match it.
+    assert_eq!(
+      super::remove_returning_freed(&mut r0_trie, &rlp_nodes, &removee_key),
+      Err(crate::ProofError::RootMismatch {
+        expected: root_before,
+        actual: root_after,
+      })
+    );
+    // Nothing more to free once the key is gone.
+    assert_eq!(
+      super::remove_returning_freed(&mut r0_trie, &rlp_nodes_after, &removee_key),
+      Ok(vec![])
+    );
+  }
+
+  #[test]
+  fn test_remove_or_prove_absent() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).unwrap();
+
+    let absent_key = key_from_nibbles("A0FF");
+    let super::RemovalOutcome::Absent(exclusion_proof) =
+      super::remove_or_prove_absent(&mut r0_trie, &rlp_nodes, &absent_key).unwrap()
+    else {
+      panic!("absent key must not be removed");
+    };
+    assert_eq!(r0_trie.hash_slow(), root);
+    assert_eq!(
+      crate::verify_key_path(root, &exclusion_proof, absent_key.as_slice()),
+      Ok(None)
+    );
+
+    let removee_key = key_from_nibbles("ABD2");
+    let (root_after, nodes_after) =
+      crate::build_alloy_trie_with_proof(&[keys[0].clone(), keys[2].clone()]);
+    assert_eq!(
+      super::remove_or_prove_absent(&mut r0_trie, &rlp_nodes, &removee_key),
+      Ok(super::RemovalOutcome::Removed {
+        root: root_after,
+        nodes: nodes_after.clone(),
+      })
+    );
+    // Nodes of the trie before removal no longer match it.
+    assert_eq!(
+      super::remove_or_prove_absent(&mut r0_trie, &rlp_nodes, &removee_key),
+      Err(crate::ProofError::RootMismatch {
+        expected: root,
+        actual: root_after,
+      })
+    );
+
+    // Returned nodes carry over to the next removal.
+    let removee_key = key_from_nibbles("E999");
+    let (root_last, nodes_last) = crate::build_alloy_trie_with_proof(&[keys[0].clone()]);
+    assert_eq!(
+      super::remove_or_prove_absent(&mut r0_trie, &nodes_after, &removee_key),
+      Ok(super::RemovalOutcome::Removed {
+        root: root_last,
+        nodes: nodes_last,
+      })
+    );
+    assert_eq!(r0_trie.hash_slow(), root_last);
+  }
+}
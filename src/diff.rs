@@ -0,0 +1,199 @@
+use alloc::vec::Vec;
+
+/// Keys that differ between two item sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrieDiff {
+  /// Keys present only in the new set.
+  pub added: Vec<alloy_primitives::Bytes>,
+  /// Keys present only in the old set.
+  pub removed: Vec<alloy_primitives::Bytes>,
+  /// Keys present in both sets, with different values.
+  pub changed: Vec<alloy_primitives::Bytes>,
+}
+
+impl TrieDiff {
+  /// Whether both sets form the same trie.
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+  }
+}
+
+/// Compares two item sets and reports which keys differ between their tries
+///
+/// # Arguments
+/// * `old` - Key-value pairs of the old trie
+/// * `new` - Key-value pairs of the new trie
+///
+/// # Returns
+/// * Added, removed and changed keys, each in ascending order
+pub fn trie_diff<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(old: &[(K, V)], new: &[(K, V)]) -> TrieDiff {
+  let mut old_iter = sort_by_key_bytes(old).into_iter().peekable();
+  let mut new_iter = sort_by_key_bytes(new).into_iter().peekable();
+
+  // Walk both sorted sets at once, advancing the one with the smaller key.
+  let mut diff = TrieDiff::default();
+  loop {
+    match (old_iter.peek().copied(), new_iter.peek().copied()) {
+      (Some((old_key, old_value)), Some((new_key, new_value))) => match old_key.cmp(new_key) {
+        core::cmp::Ordering::Less => {
+          diff
+            .removed
+            .push(alloy_primitives::Bytes::copy_from_slice(old_key));
+          old_iter.next();
+        }
+        core::cmp::Ordering::Greater => {
+          diff
+            .added
+            .push(alloy_primitives::Bytes::copy_from_slice(new_key));
+          new_iter.next();
+        }
+        core::cmp::Ordering::Equal => {
+          if old_value != new_value {
+            diff
+              .changed
+              .push(alloy_primitives::Bytes::copy_from_slice(old_key));
+          }
+          old_iter.next();
+          new_iter.next();
+        }
+      },
+      (Some((old_key, _)), None) => {
+        diff
+          .removed
+          .push(alloy_primitives::Bytes::copy_from_slice(old_key));
+        old_iter.next();
+      }
+      (None, Some((new_key, _))) => {
+        diff
+          .added
+          .push(alloy_primitives::Bytes::copy_from_slice(new_key));
+        new_iter.next();
+      }
+      (None, None) => break,
+    }
+  }
+
+  diff
+}
+
+/// Finds the path where tries of two item sets start to differ
+///
+/// Both tries are walked down from the root for as long as exactly one child differs - the
+/// returned path is of the deepest node which differs in more than one child, in its type or
+/// key, or which is missing from one of the tries. For a single changed value, this is the path
+/// of its leaf node.
+///
+/// # Arguments
+/// * `a` - Key-value pairs of the first trie
+/// * `b` - Key-value pairs of the second trie
+///
+/// # Returns
+/// * Path of the divergent node, or `None` if both roots are equal
+///
+/// # Panics
+/// * If `a` or `b` contain duplicate keys
+pub fn first_divergence<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  a: &[(K, V)],
+  b: &[(K, V)],
+) -> Option<alloy_trie::Nibbles> {
+  let nodes_by_path = |items| {
+    let (_, nodes) = crate::build_alloy_trie_with_proof_ordered(items);
+    nodes
+      .into_iter()
+      .collect::<alloy_primitives::map::HashMap<_, _>>()
+  };
+  let (nodes_a, nodes_b) = (nodes_by_path(a), nodes_by_path(b));
+
+  let mut path = alloy_trie::Nibbles::default();
+  loop {
+    let (rlp_a, rlp_b) = (nodes_a.get(&path), nodes_b.get(&path));
+    // Only possible at the root, as a single differing child is always followed.
+    if rlp_a == rlp_b {
+      return None;
+    }
+    let (Some(Ok(node_a)), Some(Ok(node_b))) = (
+      rlp_a.map(|rlp| crate::nodes::decode(rlp.as_ref())),
+      rlp_b.map(|rlp| crate::nodes::decode(rlp.as_ref())),
+    ) else {
+      return Some(path);
+    };
+
+    match (node_a, node_b) {
+      (
+        alloy_trie::nodes::TrieNode::Branch(branch_a),
+        alloy_trie::nodes::TrieNode::Branch(branch_b),
+      ) => {
+        let mut differing = (0..16).filter(|&nibble| {
+          crate::nodes::branch_child(&branch_a, nibble)
+            != crate::nodes::branch_child(&branch_b, nibble)
+        });
+        let (Some(nibble), None) = (differing.next(), differing.next()) else {
+          return Some(path);
+        };
+        path.push(nibble);
+      }
+      (
+        alloy_trie::nodes::TrieNode::Extension(extension_a),
+        alloy_trie::nodes::TrieNode::Extension(extension_b),
+      ) if extension_a.key == extension_b.key => path = path.join(&extension_a.key),
+      _ => return Some(path),
+    }
+  }
+}
+
+/// Sorts items by raw key bytes, which matches the order of trie paths.
+fn sort_by_key_bytes<K: AsRef<[u8]>, V: AsRef<[u8]>>(items: &[(K, V)]) -> Vec<(&[u8], &[u8])> {
+  let mut sorted_items = items
+    .iter()
+    .map(|(k, v)| (k.as_ref(), v.as_ref()))
+    .collect::<Vec<_>>();
+  sorted_items.sort_unstable_by_key(|(k, _)| *k);
+  sorted_items
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::{create_test_data, key_from_nibbles};
+
+  #[test]
+  fn test_trie_diff_reports_added_removed_and_changed_keys() {
+    let old = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let new = create_test_data(&[("ABC1", "1"), ("ABD2", "changed"), ("A0FF", "4")]);
+    let diff = super::trie_diff(&old, &new);
+
+    let bytes =
+      |path: &str| alloy_primitives::Bytes::copy_from_slice(key_from_nibbles(path).as_slice());
+    assert_eq!(
+      diff,
+      super::TrieDiff {
+        added: vec![bytes("A0FF")],
+        removed: vec![bytes("E999")],
+        changed: vec![bytes("ABD2")],
+      }
+    );
+  }
+
+  #[test]
+  fn test_trie_diff_of_same_items_is_empty() {
+    let old = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let mut new = old.clone();
+    new.reverse();
+    assert!(super::trie_diff(&old, &new).is_empty());
+  }
+
+  #[test]
+  fn test_first_divergence_finds_changed_leaf() {
+    let a = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    assert_eq!(super::first_divergence(&a, &a), None);
+
+    let mut b = a.clone();
+    b[1].1 = b"changed".to_vec();
+    // Leaf of `ABD2` sits in slot `D` of branch `AB`.
+    let divergence = super::first_divergence(&a, &b).unwrap();
+    assert_eq!(
+      divergence,
+      alloy_trie::Nibbles::from_nibbles([0xA, 0xB, 0xD])
+    );
+    assert!(alloy_trie::Nibbles::unpack(key_from_nibbles("ABD2")).starts_with(&divergence));
+  }
+}
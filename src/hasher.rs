@@ -0,0 +1,30 @@
+//! Hash functions referencing trie nodes from their parents.
+
+/// Hash function used to reference trie nodes by their RLP encoding
+///
+/// Nodes whose RLP is shorter than 32 bytes are inlined into their parents regardless of the hash
+/// function, so only 32-byte digests are supported. Building tries is bound to keccak256 by
+/// alloy-trie, so alternative hashers only apply to verification.
+pub trait TrieHasher {
+  /// Hashes the RLP encoding of a node.
+  fn hash(rlp: &[u8]) -> alloy_primitives::B256;
+
+  /// Root hash of an empty trie, which is the hash of `rlp("")`.
+  fn empty_root() -> alloy_primitives::B256 {
+    Self::hash(&[alloy_rlp::EMPTY_STRING_CODE])
+  }
+}
+
+/// Keccak256, as used by Ethereum - the hasher of all functions without explicit one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeccakHasher;
+
+impl TrieHasher for KeccakHasher {
+  fn hash(rlp: &[u8]) -> alloy_primitives::B256 {
+    alloy_primitives::keccak256(rlp)
+  }
+
+  fn empty_root() -> alloy_primitives::B256 {
+    crate::EMPTY_ROOT
+  }
+}
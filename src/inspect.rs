@@ -0,0 +1,342 @@
+//! Inspection of trie nodes, for debugging failed consistency checks.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Type of a trie node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+  /// Node holding the remaining key path and the value.
+  Leaf,
+  /// Node holding a shared key path and a single child.
+  Extension,
+  /// Node holding up to 16 children, one per nibble.
+  Branch,
+  /// Empty trie, encoded as an empty string.
+  Empty,
+}
+
+impl From<&alloy_trie::nodes::TrieNode> for NodeKind {
+  fn from(node: &alloy_trie::nodes::TrieNode) -> Self {
+    match node {
+      alloy_trie::nodes::TrieNode::Leaf(_) => Self::Leaf,
+      alloy_trie::nodes::TrieNode::Extension(_) => Self::Extension,
+      alloy_trie::nodes::TrieNode::Branch(_) => Self::Branch,
+      alloy_trie::nodes::TrieNode::EmptyRoot => Self::Empty,
+    }
+  }
+}
+
+/// Decodes the type of each node
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded trie nodes
+///
+/// # Returns
+/// * Kind of each node, in the same order, or the first decoding error
+pub fn classify_nodes(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<NodeKind>, crate::ProofError> {
+  rlp_nodes
+    .iter()
+    .map(|rlp| Ok(NodeKind::from(&crate::nodes::decode(rlp)?)))
+    .collect()
+}
+
+/// Counts occupied child slots of the root branch
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded trie nodes, root node first
+///
+/// # Returns
+/// * Number of the 16 slots holding a child, or `None` if the root is not a branch (or cannot be
+///   decoded)
+pub fn root_branch_fanout(rlp_nodes: &[alloy_primitives::Bytes]) -> Option<u8> {
+  let root = crate::nodes::decode(rlp_nodes.first()?).ok()?;
+  match root {
+    alloy_trie::nodes::TrieNode::Branch(branch) => Some(branch.state_mask.get().count_ones() as u8),
+    _ => None,
+  }
+}
+
+/// Renders the trie formed by given nodes as an indented tree
+///
+/// Each line shows the node kind and a truncated hash (or `inline` for nodes embedded in their
+/// parent), followed by the key nibbles of leaves and extensions and the truncated value of
+/// leaves. Children of branches are labeled by their nibble. Risc0 trie does not expose its nodes,
+/// so it can only be printed through the nodes it was built from.
+///
+/// **Only the trie encoded by `rlp_nodes` is printed** - not a Risc0 trie built from them and then
+/// changed. To print the trie after a removal, print the nodes rebuilt from its remaining leaves,
+/// as returned by [`crate::remove_or_prove_absent`] (or built with
+/// [`crate::build_alloy_trie_with_proof`] from the remaining items).
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded trie nodes, root node first
+///
+/// # Returns
+/// * Tree with one node per line, marking missing and malformed nodes instead of failing
+pub fn pretty_print(rlp_nodes: &[alloy_primitives::Bytes]) -> String {
+  let mut out = String::new();
+  let Some(root) = rlp_nodes.first() else {
+    write_line(&mut out, 0, format_args!("empty"));
+    return out;
+  };
+
+  let index = crate::nodes::NodeIndex::new(rlp_nodes);
+  let reference = short_hash(alloy_primitives::keccak256(root));
+  print_node(&mut out, &index, root, &reference, 0, "");
+  out
+}
+
+fn print_child(
+  out: &mut String,
+  index: &crate::nodes::NodeIndex<'_>,
+  child: &alloy_trie::nodes::RlpNode,
+  depth: usize,
+  label: &str,
+) {
+  match child.as_hash() {
+    Some(hash) => match index.rlp_by_hash(hash) {
+      Ok(rlp) => print_node(out, index, rlp, &short_hash(hash), depth, label),
+      Err(_) => write_line(
+        out,
+        depth,
+        format_args!("{label}unresolved {}", short_hash(hash)),
+      ),
+    },
+    None => print_node(out, index, child, "inline", depth, label),
+  }
+}
+
+fn print_node(
+  out: &mut String,
+  index: &crate::nodes::NodeIndex<'_>,
+  rlp: &[u8],
+  reference: &str,
+  depth: usize,
+  label: &str,
+) {
+  match crate::nodes::decode(rlp) {
+    Err(err) => write_line(out, depth, format_args!("{label}invalid {err:?}")),
+    Ok(alloy_trie::nodes::TrieNode::EmptyRoot) => {
+      write_line(out, depth, format_args!("{label}empty"))
+    }
+    Ok(alloy_trie::nodes::TrieNode::Leaf(leaf)) => write_line(
+      out,
+      depth,
+      format_args!(
+        "{label}leaf {reference} key={} value={}",
+        nibbles_hex(&leaf.key),
+        short_bytes(&leaf.value)
+      ),
+    ),
+    Ok(alloy_trie::nodes::TrieNode::Extension(extension)) => {
+      write_line(
+        out,
+        depth,
+        format_args!(
+          "{label}extension {reference} key={}",
+          nibbles_hex(&extension.key)
+        ),
+      );
+      print_child(out, index, &extension.child, depth + 1, "");
+    }
+    Ok(alloy_trie::nodes::TrieNode::Branch(branch)) => {
+      write_line(out, depth, format_args!("{label}branch {reference}"));
+      for nibble in 0..16 {
+        if let Some(child) = crate::nodes::branch_child(&branch, nibble) {
+          print_child(out, index, child, depth + 1, &format!("{nibble:x}: "));
+        }
+      }
+    }
+  }
+}
+
+fn write_line(out: &mut String, depth: usize, line: core::fmt::Arguments<'_>) {
+  // Writing into a string cannot fail.
+  let _ = writeln!(out, "{:indent$}{line}", "", indent = depth * 2);
+}
+
+/// Nibbles as a hex string, one digit per nibble.
+fn nibbles_hex(nibbles: &alloy_trie::Nibbles) -> String {
+  (0..nibbles.len())
+    .filter_map(|i| nibbles.get(i))
+    .filter_map(|nibble| char::from_digit(nibble.into(), 16))
+    .collect()
+}
+
+/// First 4 bytes of a hash, enough to tell nodes apart when debugging.
+fn short_hash(hash: alloy_primitives::B256) -> String {
+  format!("{}…", alloy_primitives::hex::encode_prefixed(&hash[..4]))
+}
+
+/// Value as hex, truncated to its first 8 bytes.
+fn short_bytes(bytes: &[u8]) -> String {
+  if bytes.len() <= 8 {
+    alloy_primitives::hex::encode_prefixed(bytes)
+  } else {
+    format!("{}…", alloy_primitives::hex::encode_prefixed(&bytes[..8]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::NodeKind;
+  use crate::tests::create_test_data;
+
+  /// Number of branch, extension and leaf nodes of the trie formed by given keys.
+  fn node_kind_counts(key_specs: &[(&str, &str)]) -> [usize; 3] {
+    let keys = create_test_data(key_specs);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let kinds = super::classify_nodes(&rlp_nodes).unwrap();
+    assert!(!kinds.contains(&NodeKind::Empty));
+    [NodeKind::Branch, NodeKind::Extension, NodeKind::Leaf]
+      .map(|kind| kinds.iter().filter(|&&k| k == kind).count())
+  }
+
+  #[test]
+  fn test_classify_nodes_of_collapse_cases() {
+    // Case 1: orphaned branch `B` turns into extension `B`.
+    let before = node_kind_counts(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let after = node_kind_counts(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    assert_eq!((before, after), ([3, 0, 4], [2, 1, 3]));
+
+    // Case 2: orphaned branch `B` merges with child extension `3`.
+    let before = node_kind_counts(&[
+      ("AB3C1", "1"),
+      ("AB3D2", "2"),
+      ("A0FFF", "4"),
+      ("E9999", "3"),
+    ]);
+    let after = node_kind_counts(&[("AB3C1", "1"), ("AB3D2", "2"), ("E9999", "3")]);
+    assert_eq!((before, after), ([3, 1, 4], [2, 1, 3]));
+
+    // Case 3: orphaned branch `B` merges with child leaf `1`.
+    let before = node_kind_counts(&[("AB1", "1"), ("A0F", "4"), ("E99", "3")]);
+    let after = node_kind_counts(&[("AB1", "1"), ("E99", "3")]);
+    assert_eq!((before, after), ([2, 0, 3], [1, 0, 2]));
+
+    // Case 4: extension `B` merges with parent extension `A`.
+    let before = node_kind_counts(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4")]);
+    let after = node_kind_counts(&[("ABC1", "1"), ("ABD2", "2")]);
+    assert_eq!((before, after), ([2, 1, 3], [1, 1, 2]));
+
+    // Case 5: extension `B3` merges with parent extension `A`.
+    let before = node_kind_counts(&[("AB3C1", "1"), ("AB3D2", "2"), ("A0FFF", "4")]);
+    let after = node_kind_counts(&[("AB3C1", "1"), ("AB3D2", "2")]);
+    assert_eq!((before, after), ([2, 2, 3], [1, 1, 2]));
+
+    // Case 6: leaf `B1` merges with parent extension `A`.
+    let before = node_kind_counts(&[("AB1", "1"), ("A0F", "4")]);
+    let after = node_kind_counts(&[("AB1", "1")]);
+    assert_eq!((before, after), ([1, 1, 2], [0, 0, 1]));
+  }
+
+  #[test]
+  fn test_classify_nodes_reports_malformed_node() {
+    let rlp_nodes = [alloy_primitives::Bytes::from_static(&[0xc1])];
+    assert!(super::classify_nodes(&rlp_nodes).is_err());
+  }
+
+  #[test]
+  fn test_pretty_print_renders_tree() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let tree = super::pretty_print(&rlp_nodes);
+
+    let lines = tree.lines().collect::<Vec<_>>();
+    let expected_prefixes = [
+      "branch 0x",
+      "  a: branch 0x",
+      "    0: leaf 0x",
+      "    b: branch 0x",
+      "      c: leaf 0x",
+      "      d: leaf 0x",
+      "  e: leaf 0x",
+    ];
+    assert_eq!(lines.len(), expected_prefixes.len());
+    for (line, prefix) in lines.iter().zip(expected_prefixes) {
+      assert!(
+        line.starts_with(prefix),
+        "{line:?} should start with {prefix:?}"
+      );
+    }
+    assert!(lines[2].contains("key=ff00") && lines[2].ends_with("value=0x34"));
+
+    // Proof of `E999` alone leaves the whole `A` subtree unresolved.
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_selective_proof(
+      &keys,
+      &[crate::tests::key_from_nibbles("E999")],
+    );
+    let tree = super::pretty_print(&rlp_nodes);
+    assert!(
+      tree
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("  a: unresolved 0x")
+    );
+
+    assert_eq!(super::pretty_print(&[]), "empty\n");
+  }
+
+  #[test]
+  fn test_pretty_print_after_removal() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).unwrap();
+    let removee_key = crate::tests::key_from_nibbles("A0FF");
+    let Ok(crate::RemovalOutcome::Removed { nodes, .. }) =
+      crate::remove_or_prove_absent(&mut r0_trie, &rlp_nodes, &removee_key)
+    else {
+      panic!("present key must be removed");
+    };
+
+    // Orphaned branch `A` is gone from the rebuilt nodes, replaced by extension `B`.
+    let tree = super::pretty_print(&nodes);
+    let lines = tree.lines().collect::<Vec<_>>();
+    let expected_prefixes = [
+      "branch 0x",
+      "  a: extension 0x",
+      "    branch 0x",
+      "      c: leaf 0x",
+      "      d: leaf 0x",
+      "  e: leaf 0x",
+    ];
+    assert_eq!(lines.len(), expected_prefixes.len());
+    for (line, prefix) in lines.iter().zip(expected_prefixes) {
+      assert!(
+        line.starts_with(prefix),
+        "{line:?} should start with {prefix:?}"
+      );
+    }
+    assert!(lines[1].ends_with("key=b"));
+    // Nodes before the removal still print the orphaned branch.
+    assert!(
+      super::pretty_print(&rlp_nodes)
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("  a: branch 0x")
+    );
+  }
+
+  #[test]
+  fn test_root_branch_fanout() {
+    let keys = create_test_data(&[("DA01", "1"), ("DA02", "2"), ("EA01", "3"), ("EA02", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    // Slots `D` and `E` only.
+    assert_eq!(super::root_branch_fanout(&rlp_nodes), Some(2));
+
+    let keys = create_test_data(&[("0F", "1"), ("3F", "2"), ("AF", "3"), ("FF", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::root_branch_fanout(&rlp_nodes), Some(4));
+
+    // Both keys share the `DA` prefix, so the root is an extension.
+    let keys = create_test_data(&[("DA01", "1"), ("DA02", "2")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::root_branch_fanout(&rlp_nodes), None);
+    assert_eq!(super::root_branch_fanout(&[]), None);
+  }
+}
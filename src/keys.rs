@@ -0,0 +1,40 @@
+//! Derivation of secure trie keys, as used by Ethereum state and storage tries.
+
+/// Derives the account trie key of an address, which is `keccak256(address)`.
+pub fn account_key(address: alloy_primitives::Address) -> alloy_primitives::B256 {
+  alloy_primitives::keccak256(address)
+}
+
+/// Derives the storage trie key of a slot, which is `keccak256` of its 32-byte big-endian form.
+pub fn storage_key(slot: alloy_primitives::U256) -> alloy_primitives::B256 {
+  alloy_primitives::keccak256(slot.to_be_bytes::<32>())
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn test_account_key_of_zero_address() {
+    assert_eq!(
+      super::account_key(alloy_primitives::Address::ZERO),
+      alloy_primitives::b256!("0x5380c7b7ae81a58eb98d9c78de4a1fd7fd9535fc953ed2be602daaa41767312a")
+    );
+  }
+
+  #[test]
+  fn test_storage_key_of_slot_zero() {
+    assert_eq!(
+      super::storage_key(alloy_primitives::U256::ZERO),
+      alloy_primitives::b256!("0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")
+    );
+  }
+
+  #[test]
+  fn test_storage_key_matches_b256_slot_hash() {
+    let slot = alloy_primitives::U256::from(0x1234);
+    let slot_bytes = alloy_primitives::B256::from(slot);
+    assert_eq!(
+      super::storage_key(slot),
+      alloy_primitives::keccak256(slot_bytes)
+    );
+  }
+}
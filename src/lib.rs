@@ -1,22 +1,1148 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use alloc::{vec, vec::Vec};
+
+pub mod keys;
+pub mod reth_interop;
+
+#[cfg(feature = "std")]
+mod account;
+mod collapse;
+#[cfg(feature = "std")]
+mod diff;
+mod hasher;
+mod inspect;
+mod nodes;
+mod proof;
+#[cfg(feature = "std")]
+mod secure;
+#[cfg(feature = "std")]
+mod streaming;
+mod verify;
+
+#[cfg(feature = "std")]
+pub use account::{
+  Account, AccountTrieBuilder, AccountWithStorage, StateTrieProof, StorageTrieProof,
+  build_account_with_storage, build_storage_trie, storage_root,
+};
+pub use collapse::{
+  CollapseError, CollapseKind, CollapseWitness, RemovalOutcome, RemovalStats, can_collapse,
+  predict_collapse, remove_from_proof, remove_with_root_transition,
+};
+#[cfg(feature = "std")]
+pub use collapse::{
+  CollapsibleTrie, ConsistencyError, ConsistencyStage, assert_collapse_consistency,
+  check_multi_collapse_consistency, collapse_after_removal, remove_or_prove_absent,
+  remove_returning_freed,
+};
+#[cfg(feature = "std")]
+pub use diff::{TrieDiff, first_divergence, trie_diff};
+pub use hasher::{KeccakHasher, TrieHasher};
+pub use inspect::{NodeKind, classify_nodes, pretty_print, root_branch_fanout};
+pub use proof::{
+  ProofBlobError, RlpError, SerializableProof, assert_complete_proof, decode_proof_blob,
+  encode_proof_blob, from_eth_proof, from_rlp_checked, items_from_hex, items_from_proof,
+  iter_leaves, merge_proofs, reconstruct_with_values, unresolved_nodes,
+};
+#[cfg(feature = "std")]
+pub use secure::{SecureTrieProof, build_secure_trie};
+#[cfg(feature = "std")]
+pub use streaming::{StreamingTrieBuilder, root_over_range};
+pub use verify::{
+  ProofError, authenticated_path, get_value, insert_and_verify, proven_value, update_leaf_value,
+  verify_key_path, verify_key_path_with, verify_proof, verify_proof_with, verify_value_hash,
+};
+
+/// Root hash of an empty trie, which is `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: alloy_primitives::B256 = alloy_trie::EMPTY_ROOT_HASH;
+
+/// Checks whether the root hash is the one of an empty trie.
+pub fn is_empty_root(root: alloy_primitives::B256) -> bool {
+  root == EMPTY_ROOT
+}
+
 /// Builds an Alloy trie with merkle proof for all nodes
 ///
+/// Empty values are kept as leaves holding an empty string, which Risc0 trie reconstructs as is.
+/// Note that Ethereum treats an empty value as absence of the key instead - such items should be
+/// filtered out by the caller to get the state root.
+///
 /// # Arguments
 /// * `items` - Key-value pairs to insert into the trie
 ///
 /// # Returns
-/// * Root hash and RLP-encoded proof nodes
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+/// * Nodes are deduplicated and ordered by path, root node first - the order comes from alloy-trie
+///   `into_nodes_sorted()`, so it is deterministic and independent of the order of `items`
+/// * Single item yields a lone leaf root - exactly one node, hashing to the root
+///
+/// # Panics
+/// * If `items` contain duplicate keys - use [`try_build_alloy_trie_with_proof`] to get an error
+/// * If a key is a prefix of another key (e.g. `0xAB` and `0xABCD`) - the shorter key would need a
+///   value slot in a branch node, which neither Alloy hasher nor Risc0 trie support (Risc0 fails to
+///   decode such nodes in `Trie::from_rlp`, see `test_branch_with_value`)
+#[cfg(feature = "std")]
 pub fn build_alloy_trie_with_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
   items: &[(K, V)],
 ) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
-  // Sort items by nibble representation (required by alloy-trie hasher).
-  let mut sorted_items = items.iter().collect::<Vec<_>>();
-  sorted_items.sort_by_key(|(k, _)| alloy_trie::Nibbles::unpack(k.as_ref()));
+  build_alloy_trie_with_proof_iter(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())))
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, consuming items from an iterator
+///
+/// Items may come in any order, as they are sorted internally - so they are collected once, with
+/// unpacked key paths, but the caller does not need to clone them into a slice first. For items
+/// already sorted, [`root_over_range`] computes the root without collecting them.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_iter<K, V, I>(
+  items: I,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>)
+where
+  I: IntoIterator<Item = (K, V)>,
+  K: AsRef<[u8]>,
+  V: AsRef<[u8]>,
+{
+  let sorted_items = sort_by_nibbles(items);
 
   // Collect all key paths for proof generation.
-  let proof_key_paths = sorted_items
+  let proof_key_paths = sorted_items.iter().map(|(path, _)| *path).collect();
+
+  build_alloy_trie_from_sorted(sorted_items, proof_key_paths)
+}
+
+/// Number of leaves between consecutive calls of the callback given to [`build_with_progress`].
+#[cfg(feature = "std")]
+pub const PROGRESS_INTERVAL: usize = 1024;
+
+/// Builds an Alloy trie with merkle proof for all nodes, reporting progress along the way
+///
+/// Same as [`build_alloy_trie_with_proof`], but `on_progress` is called with the number of
+/// processed and total items after every [`PROGRESS_INTERVAL`] leaves, and once more when all
+/// leaves are added (unless just reported). Sorting takes place before the first call.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `on_progress` - Callback receiving `(processed, total)` item counts
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_with_progress<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  mut on_progress: impl FnMut(usize, usize),
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+  let proof_key_paths = sorted_items.iter().map(|(path, _)| *path).collect();
+
+  let total = sorted_items.len();
+  let on_leaf = |processed: usize| {
+    if processed.is_multiple_of(PROGRESS_INTERVAL) || processed == total {
+      on_progress(processed, total);
+    }
+  };
+  let (root_hash, nodes) =
+    build_alloy_trie_from_sorted_with_progress(sorted_items, proof_key_paths, on_leaf);
+  (root_hash, dedupe_nodes(nodes))
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, from items keyed by nibble paths
+///
+/// Same as [`build_alloy_trie_with_proof`], but paths are taken as they are - no packing into
+/// byte keys and unpacking back is needed for programmatically generated paths.
+///
+/// # Arguments
+/// * `items` - Paths and values to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate paths, or paths being prefixes of other paths
+#[cfg(feature = "std")]
+pub fn build_from_nibbles<V: AsRef<[u8]>>(
+  items: &[(alloy_trie::Nibbles, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let mut sorted_items = items
     .iter()
-    .map(|(k, _)| alloy_trie::Nibbles::unpack(k.as_ref()))
+    .map(|(path, v)| (*path, v.as_ref()))
+    .collect::<Vec<_>>();
+  sorted_items.sort_by_key(|(path, _)| *path);
+
+  // Collect all key paths for proof generation.
+  let proof_key_paths = sorted_items.iter().map(|(path, _)| *path).collect();
+
+  build_alloy_trie_from_sorted(sorted_items, proof_key_paths)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, encoding values on the fly
+///
+/// Same as [`build_alloy_trie_with_proof`], but typed values are kept by the caller and each is
+/// encoded (e.g. to RLP) only when inserted.
+///
+/// # Arguments
+/// * `items` - Keys with typed values to insert into the trie
+/// * `encode` - Encoding of a value into leaf bytes
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_with_encoder<K: AsRef<[u8]> + Ord, T>(
+  items: &[(K, T)],
+  encode: impl Fn(&T) -> Vec<u8>,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  build_alloy_trie_with_proof_iter(items.iter().map(|(k, v)| (k.as_ref(), encode(v))))
+}
+
+/// Summary of an Alloy trie build, returned by [`build_alloy_trie_with_report`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildReport {
+  /// Root hash of the trie.
+  pub root: alloy_primitives::B256,
+  /// RLP-encoded proof nodes, root node first.
+  pub rlp_nodes: Vec<alloy_primitives::Bytes>,
+  /// Number of distinct trie nodes - always equal to `rlp_nodes.len()`.
+  pub node_count: usize,
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, reporting its node count
+///
+/// Same as [`build_alloy_trie_with_proof`], but saves reconstructing a Risc0 trie when only
+/// metrics are needed.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash, RLP-encoded proof nodes and their count
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_report<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> BuildReport {
+  let (root, rlp_nodes) = build_alloy_trie_with_proof(items);
+  let node_count = rlp_nodes.len();
+  BuildReport {
+    root,
+    rlp_nodes,
+    node_count,
+  }
+}
+
+/// Computes the total byte size of proof nodes retained for all items
+///
+/// Useful for budgeting witness size, e.g. for a zkVM run where proof data directly costs cycles.
+/// The size is exact, as the trie is actually built - this is not cheaper than building it.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Sum of RLP-encoded proof node lengths, or zero for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn estimate_proof_size<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(items: &[(K, V)]) -> usize {
+  let (_, rlp_nodes) = build_alloy_trie_with_proof(items);
+  rlp_nodes.iter().map(|rlp| rlp.len()).sum()
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, from items already sorted by key
+///
+/// Same as [`build_alloy_trie_with_proof`], but skips sorting - useful for data read from a sorted
+/// database. Order is only checked in debug builds. In release builds, passing unsorted items
+/// yields an undefined (wrong) root and proof nodes, without any error.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie, sorted by key in ascending order
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+/// * In debug builds, if `items` are not sorted
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_presorted<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let opts = BuildOpts {
+    skip_sort: true,
+    ..BuildOpts::default()
+  };
+  build_alloy_trie_with_proof_opts(items, opts)
+}
+
+/// Options of [`build_alloy_trie_with_proof_opts`]
+///
+/// Defaults match [`build_alloy_trie_with_proof`] - items are sorted and all nodes are retained.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildOpts {
+  /// Trust items to be already sorted by key, checking it only in debug builds.
+  pub skip_sort: bool,
+  /// Retain proof nodes - if disabled, only the root is computed.
+  pub retain_proofs: bool,
+  /// Reference every child node by hash, even if its RLP is shorter than 32 bytes.
+  ///
+  /// Non-canonical: nodes and the root differ from any spec-compliant trie over the same items.
+  /// Only meant for testing consumers (e.g. circuits) against nodes around the inlining boundary.
+  pub force_hash_children: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for BuildOpts {
+  fn default() -> Self {
+    Self {
+      skip_sort: false,
+      retain_proofs: true,
+      force_hash_children: false,
+    }
+  }
+}
+
+/// Builds an Alloy trie, with sorting and proof retention toggled by options
+///
+/// Single entry point for measuring the cost of each build stage - with default options, the
+/// result is the same as of [`build_alloy_trie_with_proof`].
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `opts` - Stages to perform
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or no nodes if proofs are not retained - both
+///   non-canonical if hash references are forced
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+/// * In debug builds, if sorting is skipped and `items` are not sorted
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_opts<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  opts: BuildOpts,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let items = items.iter().map(|(k, v)| (k.as_ref(), v.as_ref()));
+  let sorted_items = if opts.skip_sort {
+    let sorted_items = items
+      .map(|(k, v)| (alloy_trie::Nibbles::unpack(k), v))
+      .collect::<Vec<_>>();
+    debug_assert!(
+      sorted_items.is_sorted_by_key(|(path, _)| *path),
+      "items are not sorted by key"
+    );
+    sorted_items
+  } else {
+    sort_by_nibbles(items)
+  };
+
+  if opts.force_hash_children {
+    return build_hash_referenced_trie(sorted_items, opts.retain_proofs);
+  }
+
+  // Collect all key paths for proof generation, if requested.
+  let proof_key_paths = if opts.retain_proofs {
+    sorted_items.iter().map(|(path, _)| *path).collect()
+  } else {
+    Vec::new()
+  };
+
+  build_alloy_trie_from_sorted(sorted_items, proof_key_paths)
+}
+
+/// Error returned when retained proof nodes exceed the byte budget.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofTooLarge {
+  /// Total byte size of the retained proof nodes.
+  pub size: usize,
+  /// Byte budget the proof had to fit in.
+  pub max_bytes: usize,
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for ProofTooLarge {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "proof size {} bytes exceeds budget of {} bytes",
+      self.size, self.max_bytes
+    )
+  }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for ProofTooLarge {}
+
+/// Builds an Alloy trie with merkle proof for all nodes, within a byte budget
+///
+/// Same as [`build_alloy_trie_with_proof`], but fails fast when the proof would be too costly to
+/// ship, e.g. as a zkVM witness.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `max_bytes` - Maximum total byte size of the RLP-encoded proof nodes
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or an error with the measured size if it exceeds
+///   `max_bytes`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_bounded<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  max_bytes: usize,
+) -> Result<(alloy_primitives::B256, Vec<alloy_primitives::Bytes>), ProofTooLarge> {
+  let (root_hash, rlp_nodes) = build_alloy_trie_with_proof(items);
+
+  let size = rlp_nodes.iter().map(|rlp| rlp.len()).sum();
+  if size > max_bytes {
+    return Err(ProofTooLarge { size, max_bytes });
+  }
+
+  Ok((root_hash, rlp_nodes))
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, from a hash map
+///
+/// Hash map iteration order is nondeterministic, but items are sorted before building, so the
+/// output only depends on the map contents.
+///
+/// # Arguments
+/// * `map` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes
+#[cfg(feature = "std")]
+pub fn build_from_map<K: AsRef<[u8]>, V: AsRef<[u8]>, S>(
+  map: &alloy_primitives::map::HashMap<K, V, S>,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  build_alloy_trie_with_proof_iter(map)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, with keys of a fixed length
+///
+/// Same as [`build_alloy_trie_with_proof`], but the key type guarantees at compile time that all
+/// keys have length `N`, so the mixed-length keys rejected by [`try_build_alloy_trie_with_proof`]
+/// cannot be passed at all.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_fixed_trie<const N: usize, V: AsRef<[u8]>>(
+  items: &[(alloy_primitives::FixedBytes<N>, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  build_alloy_trie_with_proof(items)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, preprocessing items in parallel
+///
+/// Same as [`build_alloy_trie_with_proof`], but nibble unpacking and sorting run on the rayon
+/// thread pool. Leaves are still added sequentially, as alloy-trie hasher requires sorted input.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the empty root and no nodes for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "rayon")]
+pub fn build_alloy_trie_with_proof_par<K, V>(
+  items: &[(K, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>)
+where
+  K: AsRef<[u8]> + Ord + Sync,
+  V: AsRef<[u8]> + Sync,
+{
+  use rayon::prelude::*;
+
+  let mut sorted_items = items
+    .par_iter()
+    .map(|(k, v)| (alloy_trie::Nibbles::unpack(k), v.as_ref()))
+    .collect::<Vec<_>>();
+  sorted_items.par_sort_by_key(|(path, _)| *path);
+
+  // Collect all key paths for proof generation.
+  let proof_key_paths = sorted_items.par_iter().map(|(path, _)| *path).collect();
+
+  build_alloy_trie_from_sorted(sorted_items, proof_key_paths)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes
+///
+/// Alias of [`build_alloy_trie_with_proof`], with items sorted by their nibble paths - the same
+/// order as of their raw key bytes.
+///
+/// It used to sort items by raw key bytes rather than nibble paths. The separate variant was
+/// dropped, as both orders agree for any keys - nibbles of a byte compare as the byte itself, and
+/// a key that is a prefix of another sorts first either way (see
+/// `test_byte_and_nibble_ordering_agree_for_variable_length_keys`).
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes
+#[cfg(feature = "std")]
+#[deprecated(note = "use `build_alloy_trie_with_proof` instead")]
+pub fn alloy_hash_with_rlp<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  build_alloy_trie_with_proof(items)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, keeping the path of each node
+///
+/// Nodes are returned in ascending order of their nibble paths - a parent always precedes its
+/// children, so the root node comes first. Paths allow indexing nodes for selective transmission.
+/// Unlike [`build_alloy_trie_with_proof`], identical nodes at different paths are all kept.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, each with its path from the root
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_ordered<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> (
+  alloy_primitives::B256,
+  Vec<(alloy_trie::Nibbles, alloy_primitives::Bytes)>,
+) {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  // Collect all key paths for proof generation.
+  let proof_key_paths = sorted_items.iter().map(|(path, _)| *path).collect();
+
+  build_alloy_trie_from_sorted_with_paths(sorted_items, proof_key_paths)
+}
+
+/// Proof node, addressable by either its hash or its path.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofNode {
+  /// Keccak256 of the RLP-encoded node.
+  pub hash: alloy_primitives::B256,
+  /// Nibble path of the node from the root.
+  pub path: alloy_trie::Nibbles,
+  /// RLP-encoded node.
+  pub rlp: alloy_primitives::Bytes,
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, indexing each node by hash and path
+///
+/// Same as [`build_alloy_trie_with_proof_ordered`], with the hash of each node computed upfront.
+/// Note that nodes shorter than 32 bytes are inlined into their parents, so they are never looked
+/// up by their hash when walking the trie.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and proof nodes, ordered by their paths
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_indexed<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> (alloy_primitives::B256, Vec<ProofNode>) {
+  let (root_hash, nodes) = build_alloy_trie_with_proof_ordered(items);
+  let proof_nodes = nodes
+    .into_iter()
+    .map(|(path, rlp)| ProofNode {
+      hash: alloy_primitives::keccak256(&rlp),
+      path,
+      rlp,
+    })
     .collect();
+  (root_hash, proof_nodes)
+}
+
+/// Builds an Alloy trie with merkle proof for all nodes, keyed by keccak256 of the given keys
+///
+/// This is how account tries are keyed - by `keccak256(address)` rather than the raw 20-byte
+/// address. The trie paths are always unpacked from the 32-byte hashes.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie, with keys to be hashed
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_proof_hashed<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let hashed_items = items
+    .iter()
+    .map(|(k, v)| (alloy_primitives::keccak256(k), v.as_ref()))
+    .collect::<Vec<_>>();
+
+  build_alloy_trie_with_proof(&hashed_items)
+}
+
+/// Builds an Alloy trie with merkle proof for selected keys only
+///
+/// The root is computed over all `items`, but only nodes on the paths of `proof_keys` are
+/// retained - similar to what `eth_getProof` returns.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `proof_keys` - Keys whose proofs should be retained
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, root node first
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_alloy_trie_with_selective_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  proof_keys: &[K],
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  // Collect selected key paths for proof generation.
+  let proof_key_paths = proof_keys.iter().map(alloy_trie::Nibbles::unpack).collect();
+
+  build_alloy_trie_from_sorted(sorted_items, proof_key_paths)
+}
+
+/// Builds an Alloy trie and returns the merkle branch of a single key
+///
+/// Nodes are ordered from the root downwards, ending at the leaf of `key`, so a verifier can
+/// check each hash link sequentially. If `key` is absent, the branch ends where its path
+/// diverges from the trie instead.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `key` - Key whose branch should be returned
+///
+/// # Returns
+/// * RLP-encoded nodes on the path of `key`, root node first
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn branch_for_key<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  key: &K,
+) -> Vec<alloy_primitives::Bytes> {
+  // Only ancestors of the key are retained, and sorting them by path puts parents first.
+  let (_, rlp_nodes) = build_alloy_trie_with_selective_proof(items, core::slice::from_ref(key));
+  rlp_nodes
+}
+
+/// Builds a single merkle proof covering all of given keys
+///
+/// Ancestors shared by several keys are included only once, so the proof is smaller than the
+/// concatenation of separate proofs from [`build_proofs_for_keys`] - especially for keys under
+/// the same branch.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `keys` - Keys to be proven
+///
+/// # Returns
+/// * RLP-encoded proof nodes, deduplicated and ordered by path, root node first
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn combined_branch_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  keys: &[K],
+) -> Vec<alloy_primitives::Bytes> {
+  let (_, rlp_nodes) = build_alloy_trie_with_selective_proof(items, keys);
+  rlp_nodes
+}
+
+/// Builds an Alloy trie once and returns a separate merkle proof for each of given keys
+///
+/// A single hasher pass retains nodes for all `keys`, which are then split by path - each proof
+/// holds the nodes whose path is a prefix of its key, root node first.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `keys` - Keys whose proofs should be returned
+///
+/// # Returns
+/// * RLP-encoded proof nodes of each key
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn build_proofs_for_keys<K, V>(
+  items: &[(K, V)],
+  keys: &[K],
+) -> alloy_primitives::map::HashMap<K, Vec<alloy_primitives::Bytes>>
+where
+  K: AsRef<[u8]> + Ord + Clone + core::hash::Hash,
+  V: AsRef<[u8]>,
+{
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  // Collect requested key paths for proof generation.
+  let key_paths = keys
+    .iter()
+    .map(alloy_trie::Nibbles::unpack)
+    .collect::<Vec<_>>();
+
+  let (_, nodes) = build_alloy_trie_from_sorted_with_paths(sorted_items, key_paths.clone());
+
+  keys
+    .iter()
+    .zip(key_paths)
+    .map(|(key, key_path)| {
+      let proof = nodes
+        .iter()
+        .filter(|(path, _)| key_path.starts_with(path))
+        .map(|(_, rlp)| rlp.clone())
+        .collect();
+      (key.clone(), proof)
+    })
+    .collect()
+}
+
+/// Computes the root of an Alloy trie, without retaining any proof nodes
+///
+/// Cheaper alternative to [`build_alloy_trie_with_proof`] when only the root is needed, as no
+/// node RLP is kept in memory.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash, or the empty root for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn alloy_root_only<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> alloy_primitives::B256 {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+  assert_prefix_free(&sorted_items);
+
+  let mut hb = alloy_trie::HashBuilder::default();
+  for (path, val) in sorted_items {
+    hb.add_leaf(path, val);
+  }
+  hb.root()
+}
+
+/// Checks that two sets of items form tries with the same root
+///
+/// Order of items does not matter, so this tells whether both sets hold the same key-value pairs -
+/// only the roots are computed, making it cheaper than [`trie_diff`] when no details are needed.
+///
+/// # Arguments
+/// * `a` - Key-value pairs of the first trie
+/// * `b` - Key-value pairs of the second trie
+///
+/// # Returns
+/// * `true` if both roots are equal
+///
+/// # Panics
+/// * If `a` or `b` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn same_root<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(a: &[(K, V)], b: &[(K, V)]) -> bool {
+  alloy_root_only(a) == alloy_root_only(b)
+}
+
+/// Checks that items form a trie with the expected root
+///
+/// Cheapest check of whether data produces a known (e.g. state) root, as no proof nodes are
+/// retained - see [`alloy_root_only`].
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `expected` - Root hash the trie should have
+///
+/// # Returns
+/// * `true` if the root of the trie equals `expected`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn matches_root<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  expected: alloy_primitives::B256,
+) -> bool {
+  alloy_root_only(items) == expected
+}
+
+/// Computes the root of an Alloy trie after inserting a key-value pair
+///
+/// The trie is currently rebuilt from scratch - rehashing only the path of the new key is left as
+/// future work, so the cost is the same as of [`alloy_root_only`].
+///
+/// # Arguments
+/// * `old_items` - Key-value pairs forming the trie before insertion
+/// * `new_key` - Key to insert, replacing its old value if already present
+/// * `new_value` - Value to insert
+///
+/// # Returns
+/// * Root hash of the trie after insertion
+///
+/// # Panics
+/// * If `old_items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn update_root_on_insert<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  old_items: &[(K, V)],
+  new_key: K,
+  new_value: V,
+) -> alloy_primitives::B256 {
+  let items = old_items
+    .iter()
+    .filter(|(k, _)| k.as_ref() != new_key.as_ref())
+    .map(|(k, v)| (k.as_ref(), v.as_ref()))
+    .chain(core::iter::once((new_key.as_ref(), new_value.as_ref())))
+    .collect::<Vec<_>>();
+  alloy_root_only(&items)
+}
+
+/// Builds an Alloy trie and retains the nodes proving that `absent_key` is not part of it
+///
+/// The retained nodes follow the path where `absent_key` would live, down to the node where the
+/// path diverges - a branch with an empty slot, an extension with a different prefix, or a leaf
+/// with a different key. If `absent_key` is actually present, this is an inclusion proof instead.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `absent_key` - Key whose absence should be proven
+///
+/// # Returns
+/// * RLP-encoded proof nodes, root node first
+#[cfg(feature = "std")]
+pub fn build_exclusion_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  absent_key: &K,
+) -> Vec<alloy_primitives::Bytes> {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  // Only the path of the absent key is retained.
+  let proof_key_paths = vec![alloy_trie::Nibbles::unpack(absent_key.as_ref())];
+
+  let (_, rlp_nodes) = build_alloy_trie_from_sorted(sorted_items, proof_key_paths);
+  rlp_nodes
+}
+
+/// Builds an Alloy trie and retains the minimal nodes needed to remove `key` from it
+///
+/// Retained nodes are those on the path of `key`, plus the sibling to be pulled up when the
+/// removal leaves its parent branch with a single child - so the trie reconstructed from them
+/// can collapse without resolving anything else. The root after removal is computed by Alloy
+/// trie built without `key`, so that a verifier can check its own collapse against it.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `key` - Key to be removed
+///
+/// # Returns
+/// * Root hashes before and after the removal, and RLP-encoded nodes, root node first
+/// * If `key` is absent, both roots are the same and the nodes prove its absence
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn removal_witness<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  key: &K,
+) -> (
+  alloy_primitives::B256,
+  alloy_primitives::B256,
+  Vec<alloy_primitives::Bytes>,
+) {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+  let path = alloy_trie::Nibbles::unpack(key.as_ref());
+  let proof_key_paths = removal_proof_paths(&sorted_items, &path);
+  let position = sorted_items.binary_search_by_key(&path, |(item_path, _)| *item_path);
+
+  let (old_root, rlp_nodes) = build_alloy_trie_from_sorted(sorted_items.clone(), proof_key_paths);
+  let new_root = match position {
+    Ok(index) => {
+      let mut remaining_items = sorted_items;
+      remaining_items.remove(index);
+      build_alloy_trie_from_sorted(remaining_items, Vec::new()).0
+    }
+    Err(_) => old_root,
+  };
+  (old_root, new_root, rlp_nodes)
+}
+
+/// Paths whose proofs hold the minimal nodes needed to remove the key at `path` from the trie of
+/// already sorted items - the path itself, and the sibling to be pulled up when the removal leaves
+/// its parent branch with a single child.
+#[cfg(feature = "std")]
+fn removal_proof_paths<V>(
+  sorted_items: &[(alloy_trie::Nibbles, V)],
+  path: &alloy_trie::Nibbles,
+) -> Vec<alloy_trie::Nibbles> {
+  let mut proof_key_paths = vec![*path];
+  let Ok(index) = sorted_items.binary_search_by_key(path, |(item_path, _)| *item_path) else {
+    return proof_key_paths;
+  };
+
+  // Parent branch sits where the path diverges from the closest of its sorted neighbours.
+  let depth = [index.checked_sub(1), Some(index + 1)]
+    .into_iter()
+    .flatten()
+    .filter_map(|neighbour| sorted_items.get(neighbour))
+    .map(|(neighbour_path, _)| path.common_prefix_length(neighbour_path))
+    .max();
+
+  // Lone leaf is simply removed, leaving the empty trie.
+  if let Some(depth) = depth {
+    let prefix = path.slice(..depth);
+    let mut children = sorted_items
+      .iter()
+      .filter(|(item_path, _)| item_path.starts_with(&prefix))
+      .filter_map(|(item_path, _)| item_path.get(depth))
+      .collect::<Vec<_>>();
+    children.dedup();
+
+    // Branch left with a single child collapses into it, so the child must be resolved.
+    if let [first, second] = children[..] {
+      let sibling = if Some(first) == path.get(depth) {
+        second
+      } else {
+        first
+      };
+      let mut sibling_path = prefix;
+      sibling_path.push(sibling);
+      proof_key_paths.push(sibling_path);
+    }
+  }
+  proof_key_paths
+}
+
+/// Counts the nodes shared by proofs of two keys
+///
+/// Proof of `key_a` is retained, and the nodes whose path is also a prefix of `key_b` are counted -
+/// these are the ancestors common to both keys, from the root down to the node where their paths
+/// diverge. Useful to estimate how much witness data is saved by proving both keys at once.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+/// * `key_a` - First key
+/// * `key_b` - Second key
+///
+/// # Returns
+/// * Number of nodes shared by both proofs, or `0` for empty `items`
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+#[cfg(feature = "std")]
+pub fn common_prefix_nodes<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+  key_a: &K,
+  key_b: &K,
+) -> usize {
+  let sorted_items = sort_by_nibbles(items.iter().map(|(k, v)| (k.as_ref(), v.as_ref())));
+
+  let path_a = alloy_trie::Nibbles::unpack(key_a.as_ref());
+  let path_b = alloy_trie::Nibbles::unpack(key_b.as_ref());
+
+  let (_, nodes) = build_alloy_trie_from_sorted_with_paths(sorted_items, vec![path_a]);
+  nodes
+    .iter()
+    .filter(|(path, _)| path_b.starts_with(path))
+    .count()
+}
+
+/// Unpacks keys into nibbles once, and sorts items by them (required by alloy-trie hasher).
+#[cfg(feature = "std")]
+fn sort_by_nibbles<K: AsRef<[u8]>, V>(
+  items: impl IntoIterator<Item = (K, V)>,
+) -> Vec<(alloy_trie::Nibbles, V)> {
+  let mut sorted_items = items
+    .into_iter()
+    .map(|(k, v)| (alloy_trie::Nibbles::unpack(k), v))
+    .collect::<Vec<_>>();
+  sorted_items.sort_by_key(|(path, _)| *path);
+  sorted_items
+}
+
+/// Panics on duplicate keys and on keys being prefixes of other keys. Both are adjacent after
+/// sorting, and would otherwise corrupt the hasher state.
+#[cfg(feature = "std")]
+fn assert_prefix_free<V>(sorted_items: &[(alloy_trie::Nibbles, V)]) {
+  let Some(pair) = sorted_items
+    .windows(2)
+    .find(|pair| pair[1].0.starts_with(&pair[0].0))
+  else {
+    return;
+  };
+
+  let key = alloy_primitives::hex::encode_prefixed(pair[0].0.pack());
+  if pair[0].0 == pair[1].0 {
+    panic!("duplicate key {key}");
+  }
+  panic!(
+    "key {key} is a prefix of key {}",
+    alloy_primitives::hex::encode_prefixed(pair[1].0.pack())
+  );
+}
+
+/// Builds a trie from already sorted items with every child referenced by hash.
+///
+/// Alloy hasher always inlines short nodes, so the nodes are encoded here instead - returned in
+/// path order, root node first, like the nodes retained by the hasher.
+#[cfg(feature = "std")]
+fn build_hash_referenced_trie<V: AsRef<[u8]>>(
+  sorted_items: Vec<(alloy_trie::Nibbles, V)>,
+  retain_proofs: bool,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  if sorted_items.is_empty() {
+    return (alloy_trie::EMPTY_ROOT_HASH, Vec::new());
+  }
+  assert_prefix_free(&sorted_items);
+
+  let mut nodes = Vec::new();
+  let root_rlp = encode_hash_referenced_node(&sorted_items, 0, &mut nodes);
+  let root_hash = alloy_primitives::keccak256(&root_rlp);
+  if !retain_proofs {
+    return (root_hash, Vec::new());
+  }
+
+  nodes.sort_by_key(|(path, _)| *path);
+  (root_hash, dedupe_nodes(nodes))
+}
+
+/// Encodes the node of the subtrie over items sharing the first `depth` nibbles, collecting it
+/// and all its descendants along with their paths.
+#[cfg(feature = "std")]
+fn encode_hash_referenced_node<V: AsRef<[u8]>>(
+  items: &[(alloy_trie::Nibbles, V)],
+  depth: usize,
+  nodes: &mut Vec<(alloy_trie::Nibbles, alloy_primitives::Bytes)>,
+) -> alloy_primitives::Bytes {
+  let hash_reference =
+    |rlp: &[u8]| alloy_trie::nodes::RlpNode::word_rlp(&alloy_primitives::keccak256(rlp));
+
+  let (first_path, last_path) = (&items[0].0, &items[items.len() - 1].0);
+  let rlp = if let [(path, value)] = items {
+    alloy_rlp::encode(alloy_trie::nodes::LeafNode::new(
+      path.slice(depth..),
+      value.as_ref().to_vec(),
+    ))
+  } else {
+    let shared = first_path
+      .slice(depth..)
+      .common_prefix_length(&last_path.slice(depth..));
+    if shared > 0 {
+      let child = encode_hash_referenced_node(items, depth + shared, nodes);
+      alloy_rlp::encode(alloy_trie::nodes::ExtensionNode::new(
+        first_path.slice(depth..depth + shared),
+        hash_reference(&child),
+      ))
+    } else {
+      let mut stack = Vec::new();
+      let mut state_mask = alloy_trie::TrieMask::default();
+      for group in items.chunk_by(|a, b| a.0.get(depth) == b.0.get(depth)) {
+        let nibble = group[0].0.get(depth).expect("keys are prefix-free");
+        let child = encode_hash_referenced_node(group, depth + 1, nodes);
+        state_mask.set_bit(nibble);
+        stack.push(hash_reference(&child));
+      }
+      alloy_rlp::encode(alloy_trie::nodes::BranchNode::new(stack, state_mask))
+    }
+  };
+
+  let rlp = alloy_primitives::Bytes::from(rlp);
+  nodes.push((first_path.slice(..depth), rlp.clone()));
+  rlp
+}
+
+/// Feeds already sorted items into the alloy trie hasher, retaining proofs for given key paths.
+///
+/// Returned nodes are deduplicated - identical nodes at different paths (e.g. short inlined leaves)
+/// are kept only once, at their first position.
+#[cfg(feature = "std")]
+fn build_alloy_trie_from_sorted<V: AsRef<[u8]>>(
+  sorted_items: Vec<(alloy_trie::Nibbles, V)>,
+  proof_key_paths: Vec<alloy_trie::Nibbles>,
+) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+  let (root_hash, nodes) = build_alloy_trie_from_sorted_with_paths(sorted_items, proof_key_paths);
+  (root_hash, dedupe_nodes(nodes))
+}
+
+/// Drops paths of retained nodes, keeping only the first of identical nodes.
+#[cfg(feature = "std")]
+fn dedupe_nodes(
+  nodes: Vec<(alloy_trie::Nibbles, alloy_primitives::Bytes)>,
+) -> Vec<alloy_primitives::Bytes> {
+  let mut seen = alloy_primitives::map::HashSet::default();
+  nodes
+    .into_iter()
+    .map(|(_, rlp)| rlp)
+    .filter(|rlp| seen.insert(alloy_primitives::keccak256(rlp)))
+    .collect()
+}
+
+/// Same as [`build_alloy_trie_from_sorted`], but keeps the path of each retained node.
+#[cfg(feature = "std")]
+fn build_alloy_trie_from_sorted_with_paths<V: AsRef<[u8]>>(
+  sorted_items: Vec<(alloy_trie::Nibbles, V)>,
+  proof_key_paths: Vec<alloy_trie::Nibbles>,
+) -> (
+  alloy_primitives::B256,
+  Vec<(alloy_trie::Nibbles, alloy_primitives::Bytes)>,
+) {
+  build_alloy_trie_from_sorted_with_progress(sorted_items, proof_key_paths, |_| {})
+}
+
+/// Same as [`build_alloy_trie_from_sorted_with_paths`], calling `on_leaf` with the number of
+/// leaves added so far after each leaf.
+#[cfg(feature = "std")]
+fn build_alloy_trie_from_sorted_with_progress<V: AsRef<[u8]>>(
+  sorted_items: Vec<(alloy_trie::Nibbles, V)>,
+  proof_key_paths: Vec<alloy_trie::Nibbles>,
+  mut on_leaf: impl FnMut(usize),
+) -> (
+  alloy_primitives::B256,
+  Vec<(alloy_trie::Nibbles, alloy_primitives::Bytes)>,
+) {
+  // Empty trie has a well-known root and no nodes to prove.
+  if sorted_items.is_empty() {
+    return (EMPTY_ROOT, Vec::new());
+  }
+
+  assert_prefix_free(&sorted_items);
 
   // Create alloy trie hasher, with proof retainer.
   let hb = alloy_trie::HashBuilder::default();
@@ -24,22 +1150,158 @@ pub fn build_alloy_trie_with_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
   let mut hb = hb.with_proof_retainer(proof_retainer);
 
   // PInsert all items.
-  for (key, val) in sorted_items {
-    hb.add_leaf(alloy_trie::Nibbles::unpack(key), val.as_ref());
+  for (index, (path, val)) in sorted_items.into_iter().enumerate() {
+    hb.add_leaf(path, val.as_ref());
+    on_leaf(index + 1);
   }
 
   // Compute root to finalize internal state and make proof nodes available.
   let root_hash = hb.root();
 
-  // Get RLP from proof nodes.
-  let rlp_nodes: Vec<alloy_primitives::Bytes> = hb
-    .take_proof_nodes()
-    .into_nodes_sorted()
-    .into_iter()
-    .map(|(_, rlp)| rlp)
-    .collect();
+  // Get RLP from proof nodes, ordered by their paths.
+  let nodes = hb.take_proof_nodes().into_nodes_sorted();
+
+  (root_hash, nodes)
+}
+
+/// Errors returned when the provided items cannot form a valid trie.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieBuildError {
+  /// No items were provided.
+  EmptyInput,
+  /// The same key was provided more than once.
+  DuplicateKey(alloy_primitives::Bytes),
+  /// The key length differs from the length of the first key.
+  InconsistentKeyLength {
+    key: alloy_primitives::Bytes,
+    expected: usize,
+  },
+  /// The key sorts before the previously added key.
+  UnsortedKey(alloy_primitives::Bytes),
+  /// The previously added key is a prefix of the key, which would need a value slot in a branch.
+  PrefixKey(alloy_primitives::Bytes),
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for TrieBuildError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::EmptyInput => write!(f, "no items provided"),
+      Self::DuplicateKey(key) => write!(f, "duplicate key {key}"),
+      Self::InconsistentKeyLength { key, expected } => {
+        write!(f, "key {key} has length {}, expected {expected}", key.len())
+      }
+      Self::UnsortedKey(key) => write!(f, "key {key} sorts before the previous key"),
+      Self::PrefixKey(key) => write!(f, "key {key} extends the previous key"),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for TrieBuildError {}
+
+/// Fallible version of [`build_alloy_trie_with_proof`]
+///
+/// Validates the input before building, so that malformed items are reported instead of
+/// producing a wrong root or a panic.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or the first validation error found
+#[cfg(feature = "std")]
+pub fn try_build_alloy_trie_with_proof<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> Result<(alloy_primitives::B256, Vec<alloy_primitives::Bytes>), TrieBuildError> {
+  validate_items(items)?;
+  Ok(build_alloy_trie_with_proof(items))
+}
+
+/// Error returned when Risc0 trie reconstructed from Alloy proof nodes has a different root.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MismatchError {
+  /// Root computed by Alloy trie hasher.
+  pub alloy_root: alloy_primitives::B256,
+  /// Root of Risc0 trie reconstructed from the proof nodes.
+  pub risc0_root: alloy_primitives::B256,
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for MismatchError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "alloy root {} differs from risc0 root {}",
+      self.alloy_root, self.risc0_root
+    )
+  }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for MismatchError {}
+
+/// Self-checking version of [`build_alloy_trie_with_proof`]
+///
+/// Proof nodes are reconstructed into a Risc0 trie, and returned only if its root agrees with the
+/// one computed by Alloy - catching any divergence between the implementations at the source.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie
+///
+/// # Returns
+/// * Root hash and RLP-encoded proof nodes, or both roots if they disagree
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+/// * If Risc0 trie cannot decode the nodes produced by Alloy
+#[cfg(feature = "std")]
+pub fn build_checked<K: AsRef<[u8]> + Ord, V: AsRef<[u8]>>(
+  items: &[(K, V)],
+) -> Result<(alloy_primitives::B256, Vec<alloy_primitives::Bytes>), MismatchError> {
+  let (alloy_root, rlp_nodes) = build_alloy_trie_with_proof(items);
+  let risc0_root = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes)
+    .expect("alloy nodes should decode in risc0")
+    .hash_slow();
+
+  if alloy_root != risc0_root {
+    return Err(MismatchError {
+      alloy_root,
+      risc0_root,
+    });
+  }
+  Ok((alloy_root, rlp_nodes))
+}
+
+/// Checks that items are non-empty, have keys of equal length and contain no duplicate keys.
+#[cfg(feature = "std")]
+fn validate_items<K: AsRef<[u8]>, V>(items: &[(K, V)]) -> Result<(), TrieBuildError> {
+  let Some((first_key, _)) = items.first() else {
+    return Err(TrieBuildError::EmptyInput);
+  };
+
+  // Keys of different length would make some keys prefixes of others (or diverge at the leaf
+  // level), which alloy-trie does not support.
+  let expected = first_key.as_ref().len();
+  if let Some((key, _)) = items.iter().find(|(k, _)| k.as_ref().len() != expected) {
+    return Err(TrieBuildError::InconsistentKeyLength {
+      key: alloy_primitives::Bytes::copy_from_slice(key.as_ref()),
+      expected,
+    });
+  }
 
-  (root_hash, rlp_nodes)
+  // With equal lengths, duplicates end up adjacent after sorting.
+  let mut sorted_keys = items.iter().map(|(k, _)| k.as_ref()).collect::<Vec<_>>();
+  sorted_keys.sort_unstable();
+  if let Some(pair) = sorted_keys.windows(2).find(|pair| pair[0] == pair[1]) {
+    return Err(TrieBuildError::DuplicateKey(
+      alloy_primitives::Bytes::copy_from_slice(pair[0]),
+    ));
+  }
+
+  Ok(())
 }
 
 #[cfg(test)]
@@ -59,48 +1321,39 @@ mod tests {
     keys: Vec<(alloy_primitives::B256, Vec<u8>)>,
     removee_key: alloy_primitives::B256,
   ) {
-    let mut keys_with_removee = keys.clone();
-    keys_with_removee.push((removee_key, b"removee".to_vec()));
-
-    // Build trie without removee key.
-    let (alloy_hash_before, rlp_nodes_before) = super::build_alloy_trie_with_proof(&keys);
-    println!("Alloy hash before: {:?}", alloy_hash_before);
-
-    let r0_trie_before = Trie::from_rlp(rlp_nodes_before).unwrap();
-    let r0_hash_before = r0_trie_before.hash_slow();
-    println!("Risc0 root hash before: {:?}", r0_hash_before);
-    assert_eq!(alloy_hash_before, r0_hash_before);
-
-    // Build trie with removee key.
-    let (alloy_hash_after, rlp_nodes_after) =
-      super::build_alloy_trie_with_proof(&keys_with_removee);
-    println!("Alloy hash after: {:?}", alloy_hash_after);
-
-    let r0_trie_after = Trie::from_rlp(rlp_nodes_after).unwrap();
-    let r0_hash_after = r0_trie_after.hash_slow();
-    println!("Risc0 root hash after: {:?}", r0_hash_after);
-    assert_eq!(alloy_hash_after, r0_hash_after);
+    if let Err(err) = super::assert_collapse_consistency(&keys, &removee_key) {
+      panic!("{err}");
+    }
+  }
 
-    // Get rid of removee key from the latter Risc0 trie and compare.
-    let mut r0_trie_modified = r0_trie_after;
-    let is_removed = r0_trie_modified.remove(&removee_key);
-    assert_eq!(true, is_removed);
-    let r0_hash_modified = r0_trie_modified.hash_slow();
-    println!(
-      "Risc0 root hash after removee key removal: {:?}",
-      r0_hash_modified
-    );
-    assert_eq!(r0_hash_modified, r0_hash_before);
+  /// Same as [`check_trie_consistency_with_removee`], but every value (including the removee's)
+  /// is replaced with `value_size` bytes, unique per key.
+  fn check_trie_consistency_with_value_size(
+    keys: Vec<(alloy_primitives::B256, Vec<u8>)>,
+    removee_key: alloy_primitives::B256,
+    value_size: usize,
+  ) {
+    let keys = keys
+      .into_iter()
+      .enumerate()
+      .map(|(i, (key, _))| (key, vec![i as u8 + 1; value_size]))
+      .collect::<Vec<_>>();
+    let removee = [(removee_key, vec![0xFF; value_size])];
+    if let Err(err) = super::check_multi_collapse_consistency(&keys, &removee) {
+      panic!("{err}");
+    }
   }
 
   /// Helper function to create a B256 from a hex string, automatically right-padding with zeros.
-  fn key_from_nibbles(path: &str) -> alloy_primitives::B256 {
+  pub(crate) fn key_from_nibbles(path: &str) -> alloy_primitives::B256 {
     let path_padded: String = format!("{:0<64}", path);
     path_padded.parse().expect("Invalid hex string")
   }
 
   /// Create test data with cleaner key definitions
-  fn create_test_data(key_specs: &[(&str, &str)]) -> Vec<(alloy_primitives::B256, Vec<u8>)> {
+  pub(crate) fn create_test_data(
+    key_specs: &[(&str, &str)],
+  ) -> Vec<(alloy_primitives::B256, Vec<u8>)> {
     key_specs
       .iter()
       .map(|(key_hex, value)| (key_from_nibbles(key_hex), value.as_bytes().to_vec()))
@@ -142,10 +1395,971 @@ mod tests {
     check_trie_consistency_with_removee(keys, removee_key);
   }
 
+  #[test]
+  fn test_extension_into_extension_merge_with_long_segments() {
+    // Parent extension `1234A` -> orphaned branch -> child extension `5678`, which must all merge
+    // into a single extension `1234AB5678` after the removal.
+    let keys = create_test_data(&[("1234AB5678C1", "1"), ("1234AB5678D2", "2")]);
+    let removee_key = key_from_nibbles("1234A0");
+    check_trie_consistency_with_removee(keys.clone(), removee_key);
+
+    let (_, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let alloy_trie::nodes::TrieNode::Extension(extension) =
+      crate::nodes::decode(&rlp_nodes[0]).unwrap()
+    else {
+      panic!("expected extension root node");
+    };
+    assert_eq!(
+      extension.key,
+      alloy_trie::Nibbles::from_nibbles([0x1, 0x2, 0x3, 0x4, 0xA, 0xB, 0x5, 0x6, 0x7, 0x8])
+    );
+  }
+
   #[test]
   fn test_case6_collapse_with_parent_extension_and_child_leaf() {
     let keys = create_test_data(&[("AB1", "1")]);
     let removee_key = key_from_nibbles("A0F");
     check_trie_consistency_with_removee(keys, removee_key);
   }
+
+  #[test]
+  fn test_all_cases_collapse_with_large_values() {
+    let cases: [(&[(&str, &str)], &str); 6] = [
+      (&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")], "A0FF"),
+      (&[("AB3C1", "1"), ("AB3D2", "2"), ("E9999", "3")], "A0FFF"),
+      (&[("AB1", "1"), ("E99", "2")], "A0F"),
+      (&[("ABC1", "1"), ("ABD2", "2")], "A0FF"),
+      (&[("AB3C1", "1"), ("AB3D2", "2")], "A0FFF"),
+      (&[("AB1", "1")], "A0F"),
+    ];
+    for (keys, removee) in cases {
+      check_trie_consistency_with_value_size(
+        create_test_data(keys),
+        key_from_nibbles(removee),
+        100,
+      );
+    }
+  }
+
+  #[test]
+  fn test_all_cases_collapse_across_inline_boundary() {
+    // Padded 32-byte keys give every leaf 34+ bytes, so the cases above never inline a node. The
+    // same layouts over 2- and 3-byte keys inline most nodes with 1-byte values, and none of them
+    // with 100-byte values. Counts of hash-referenced nodes derived with a standalone keccak256
+    // and RLP script, for the tries holding the removee.
+    let cases: [(&[&[u8]], &[u8], usize, usize); 6] = [
+      (
+        &[&[0xAB, 0xC1], &[0xAB, 0xD2], &[0xE9, 0x99]],
+        &[0xA0, 0xFF],
+        2,
+        7,
+      ),
+      (
+        &[
+          &[0xAB, 0x3C, 0x10],
+          &[0xAB, 0x3D, 0x20],
+          &[0xE9, 0x99, 0x90],
+        ],
+        &[0xA0, 0xFF, 0xF0],
+        2,
+        8,
+      ),
+      (&[&[0xAB], &[0xE9]], &[0xA0], 1, 5),
+      (&[&[0xAB, 0xC1], &[0xAB, 0xD2]], &[0xA0, 0xFF], 2, 6),
+      (
+        &[&[0xAB, 0x3C, 0x10], &[0xAB, 0x3D, 0x20]],
+        &[0xA0, 0xFF, 0xF0],
+        2,
+        7,
+      ),
+      (&[&[0xAB]], &[0xA0], 1, 4),
+    ];
+    let hash_referenced = |value_size: usize, keys: &[&[u8]], removee: &[u8]| {
+      let mut items = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.to_vec(), vec![i as u8 + 1; value_size]))
+        .collect::<Vec<_>>();
+      let removee = [(removee.to_vec(), vec![0xFF; value_size])];
+      if let Err(err) = super::check_multi_collapse_consistency(&items, &removee) {
+        panic!("{err}");
+      }
+      items.extend(removee);
+      let (root, rlp_nodes) = super::build_alloy_trie_with_proof(&items);
+      rlp_nodes
+        .iter()
+        .filter(|rlp| rlp.len() >= 32 || alloy_primitives::keccak256(rlp) == root)
+        .count()
+    };
+    for (keys, removee, small, large) in cases {
+      assert_eq!(hash_referenced(1, keys, removee), small);
+      assert_eq!(hash_referenced(100, keys, removee), large);
+    }
+  }
+
+  #[test]
+  fn test_try_build_rejects_empty_input() {
+    let keys: Vec<(alloy_primitives::B256, Vec<u8>)> = vec![];
+    let result = super::try_build_alloy_trie_with_proof(&keys);
+    assert_eq!(result, Err(super::TrieBuildError::EmptyInput));
+  }
+
+  #[test]
+  fn test_try_build_rejects_inconsistent_key_length() {
+    let keys = vec![
+      (vec![0xAB, 0xCD], b"1".to_vec()),
+      (vec![0xAB], b"2".to_vec()),
+    ];
+    let result = super::try_build_alloy_trie_with_proof(&keys);
+    assert_eq!(
+      result,
+      Err(super::TrieBuildError::InconsistentKeyLength {
+        key: alloy_primitives::Bytes::from(vec![0xAB]),
+        expected: 2,
+      })
+    );
+  }
+
+  #[test]
+  fn test_try_build_matches_infallible_build() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let result = super::try_build_alloy_trie_with_proof(&keys);
+    assert_eq!(result, Ok(super::build_alloy_trie_with_proof(&keys)));
+  }
+
+  #[test]
+  fn test_byte_and_nibble_ordering_agree_for_variable_length_keys() {
+    // No key is a prefix of another, but byte lengths differ.
+    let keys: Vec<(Vec<u8>, Vec<u8>)> = vec![
+      (vec![0x13], b"1".to_vec()),
+      (vec![0x12, 0x34], b"2".to_vec()),
+      (vec![0x12, 0x35, 0x00], b"3".to_vec()),
+      (vec![0xE0, 0x00, 0x00, 0x01], b"4".to_vec()),
+      (vec![0x7F], b"5".to_vec()),
+    ];
+
+    let mut byte_sorted = keys.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+    byte_sorted.sort();
+    let nibble_sorted =
+      super::sort_by_nibbles(keys.iter().map(|(k, v)| (k.as_slice(), v.as_slice())))
+        .into_iter()
+        .map(|(path, _)| path.pack().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(byte_sorted, nibble_sorted);
+
+    let (alloy_hash, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), alloy_hash);
+  }
+
+  #[test]
+  fn test_exclusion_proof_reconstructs_trie_without_absent_key() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, _) = super::build_alloy_trie_with_proof(&keys);
+
+    // Path of the absent key diverges at the extension below the root branch.
+    let absent_key = key_from_nibbles("A0FF");
+    let rlp_nodes = super::build_exclusion_proof(&keys, &absent_key);
+
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+    assert_eq!(r0_trie.get(absent_key), None);
+  }
+
+  #[test]
+  fn test_empty_trie_roundtrip() {
+    let keys: Vec<(alloy_primitives::B256, Vec<u8>)> = vec![];
+    let (alloy_hash, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(alloy_hash, alloy_trie::EMPTY_ROOT_HASH);
+    assert!(rlp_nodes.is_empty());
+
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), alloy_trie::EMPTY_ROOT_HASH);
+  }
+
+  #[test]
+  fn test_build_checked_matches_unchecked_build() {
+    let keys = create_test_data(&[
+      ("AB3C1", "1"),
+      ("AB3D2", "2"),
+      ("A0FFF", "3"),
+      ("E9999", "4"),
+    ]);
+    assert_eq!(
+      super::build_checked(&keys),
+      Ok(super::build_alloy_trie_with_proof(&keys))
+    );
+  }
+
+  #[test]
+  fn test_try_build_rejects_duplicate_keys() {
+    let duplicate_key = key_from_nibbles("ABC1");
+    let keys = vec![
+      (duplicate_key, b"1".to_vec()),
+      (key_from_nibbles("E999"), b"2".to_vec()),
+      (duplicate_key, b"3".to_vec()),
+    ];
+    let result = super::try_build_alloy_trie_with_proof(&keys);
+    assert_eq!(
+      result,
+      Err(super::TrieBuildError::DuplicateKey(
+        alloy_primitives::Bytes::copy_from_slice(duplicate_key.as_slice())
+      ))
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "duplicate key")]
+  fn test_build_panics_on_duplicate_keys() {
+    let duplicate_key = key_from_nibbles("ABC1");
+    let keys = vec![
+      (duplicate_key, b"1".to_vec()),
+      (duplicate_key, b"2".to_vec()),
+    ];
+    super::build_alloy_trie_with_proof(&keys);
+  }
+
+  #[test]
+  #[should_panic(expected = "key 0xab is a prefix of key 0xabcd")]
+  fn test_prefix_keys() {
+    // Value of `0xAB` would have to live in the 17th slot of the branch at `AB`, which is not
+    // supported - building must fail loudly instead of producing a root Risc0 does not agree on.
+    let keys = vec![
+      (vec![0xAB], b"1".to_vec()),
+      (vec![0xAB, 0xCD], b"2".to_vec()),
+      (vec![0xE0], b"3".to_vec()),
+    ];
+    super::build_alloy_trie_with_proof(&keys);
+  }
+
+  #[test]
+  fn test_branch_with_value() {
+    // Keys `0xAB` => "1" and `0xABCD` => "2" need a branch at `AB` holding a value. Alloy hasher
+    // cannot build it (see `test_prefix_keys`), so the nodes are encoded by hand.
+    let leaf = [0xC2, 0x3D, b'2'];
+    let mut branch = vec![0xD3];
+    branch.extend([alloy_rlp::EMPTY_STRING_CODE; 12]);
+    branch.extend(leaf);
+    branch.extend([alloy_rlp::EMPTY_STRING_CODE; 3]);
+    branch.push(b'1');
+    let mut extension = vec![0xD7, 0x82, 0x00, 0xAB];
+    extension.extend(&branch);
+
+    // Alloy rejects the branch instead of decoding it without the value.
+    assert!(crate::nodes::decode(&branch).is_err());
+
+    // Risc0 has no branch values either, and rejects the inlined branch rather than dropping it.
+    assert!(Trie::from_rlp([&extension]).is_err());
+  }
+
+  #[test]
+  fn test_hashed_keys_match_manual_hashing() {
+    let accounts = vec![
+      (
+        alloy_primitives::address!("0x0000000000000000000000000000000000000000"),
+        b"zero".to_vec(),
+      ),
+      (
+        alloy_primitives::address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"),
+        b"vitalik".to_vec(),
+      ),
+      (
+        alloy_primitives::address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        b"weth".to_vec(),
+      ),
+    ];
+    let (root, rlp_nodes) = super::build_alloy_trie_with_proof_hashed(&accounts);
+
+    let manually_hashed = accounts
+      .iter()
+      .map(|(address, value)| (alloy_primitives::keccak256(address), value.clone()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      (root, rlp_nodes.clone()),
+      super::build_alloy_trie_with_proof(&manually_hashed)
+    );
+
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+    for (hashed_key, value) in &manually_hashed {
+      assert_eq!(r0_trie.get(hashed_key), Some(value.as_slice()));
+    }
+  }
+
+  #[cfg(feature = "rayon")]
+  #[test]
+  fn test_parallel_build_matches_sequential_build() {
+    let keys = (0..1_000u64)
+      .map(|i| {
+        (
+          alloy_primitives::keccak256(i.to_be_bytes()),
+          i.to_be_bytes().to_vec(),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      super::build_alloy_trie_with_proof_par(&keys),
+      super::build_alloy_trie_with_proof(&keys)
+    );
+  }
+
+  #[test]
+  fn test_iter_build_matches_slice_build() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let streamed = keys.clone().into_iter().rev();
+    assert_eq!(
+      super::build_alloy_trie_with_proof_iter(streamed),
+      super::build_alloy_trie_with_proof(&keys)
+    );
+  }
+
+  #[test]
+  fn test_root_only_matches_proof_build() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let (root, _) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::alloy_root_only(&keys), root);
+
+    let no_keys: Vec<(alloy_primitives::B256, Vec<u8>)> = vec![];
+    assert_eq!(
+      super::alloy_root_only(&no_keys),
+      alloy_trie::EMPTY_ROOT_HASH
+    );
+  }
+
+  #[test]
+  fn test_selective_proof_covers_only_selected_keys() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (full_root, full_nodes) = super::build_alloy_trie_with_proof(&keys);
+
+    let selected_key = key_from_nibbles("ABC1");
+    let (root, rlp_nodes) = super::build_alloy_trie_with_selective_proof(&keys, &[selected_key]);
+    assert_eq!(root, full_root);
+    assert!(rlp_nodes.len() < full_nodes.len());
+    assert!(rlp_nodes.iter().all(|node| full_nodes.contains(node)));
+
+    // Selected key verifies against the root.
+    assert_eq!(
+      super::verify_proof(root, &rlp_nodes, selected_key.as_slice(), b"1"),
+      Ok(())
+    );
+
+    // Path of a non-selected key is not part of the proof.
+    let non_selected_key = key_from_nibbles("E999");
+    let result = alloy_trie::proof::verify_proof(
+      root,
+      alloy_trie::Nibbles::unpack(non_selected_key),
+      Some(b"3".to_vec()),
+      &rlp_nodes,
+    );
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_ordered_proof_paths_are_strictly_increasing() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "4"), ("E999", "3")]);
+    let (root, nodes) = super::build_alloy_trie_with_proof_ordered(&keys);
+    assert!(nodes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(nodes[0].0.is_empty());
+
+    let (expected_root, expected_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(root, expected_root);
+    let rlp_nodes = nodes.into_iter().map(|(_, rlp)| rlp).collect::<Vec<_>>();
+    assert_eq!(rlp_nodes, expected_nodes);
+  }
+
+  #[test]
+  fn test_long_values_roundtrip() {
+    // Values around the RLP short/long string boundary (55 bytes).
+    let keys = [1, 32, 55, 56, 200]
+      .into_iter()
+      .enumerate()
+      .map(|(i, len)| (key_from_nibbles(&format!("{i:X}F")), vec![0xAB; len]))
+      .collect::<Vec<_>>();
+
+    let (alloy_hash, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), alloy_hash);
+    for (key, value) in &keys {
+      assert_eq!(r0_trie.get(key), Some(value.as_slice()));
+    }
+  }
+
+  #[test]
+  fn test_proof_nodes_are_deduplicated() {
+    // Both leaves are short enough to be inlined, and identical below the root branch.
+    let keys = vec![
+      (vec![0xA1, 0x11], b"x".to_vec()),
+      (vec![0xB1, 0x11], b"x".to_vec()),
+      (vec![0xC2, 0x22], b"y".to_vec()),
+    ];
+    let (_, nodes_with_paths) = super::build_alloy_trie_with_proof_ordered(&keys);
+    let (root, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert!(rlp_nodes.len() < nodes_with_paths.len());
+
+    for (i, node) in rlp_nodes.iter().enumerate() {
+      assert!(!rlp_nodes[i + 1..].contains(node));
+    }
+
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+  }
+
+  #[test]
+  fn test_fixed_trie_matches_flexible_build() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    assert_eq!(
+      super::build_fixed_trie(&keys),
+      super::build_alloy_trie_with_proof(&keys)
+    );
+
+    let short_keys = vec![
+      (
+        alloy_primitives::FixedBytes::<4>::new([0xAB, 0xC1, 0, 0]),
+        b"1".to_vec(),
+      ),
+      (
+        alloy_primitives::FixedBytes::<4>::new([0xE9, 0x99, 0, 0]),
+        b"2".to_vec(),
+      ),
+    ];
+    let (root, rlp_nodes) = super::build_fixed_trie(&short_keys);
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+  }
+
+  #[test]
+  fn test_build_report_counts_nodes() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let report = super::build_alloy_trie_with_report(&keys);
+    let (root, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(report.root, root);
+    assert_eq!(report.rlp_nodes, rlp_nodes);
+    assert_eq!(report.node_count, rlp_nodes.len());
+
+    let empty_report = super::build_alloy_trie_with_report::<alloy_primitives::B256, Vec<u8>>(&[]);
+    assert_eq!(empty_report.root, alloy_trie::EMPTY_ROOT_HASH);
+    assert_eq!(empty_report.node_count, 0);
+  }
+
+  #[test]
+  fn test_node_count_matches() {
+    // With proofs retained for all keys, every node is in the proof exactly once. Values as long
+    // as hashes keep all leaves from being inlined, so Risc0 reconstructs each node separately.
+    let keys = (0..256u64)
+      .map(|i| {
+        let key = alloy_primitives::keccak256(i.to_be_bytes());
+        (key, alloy_primitives::keccak256(key).to_vec())
+      })
+      .collect::<Vec<_>>();
+    let (root, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+
+    let r0_trie = Trie::from_rlp(&rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+    assert_eq!(r0_trie.size(), rlp_nodes.len());
+  }
+
+  #[test]
+  fn test_estimate_proof_size_sums_node_lengths() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (_, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let expected = rlp_nodes.iter().map(|rlp| rlp.len()).sum::<usize>();
+    assert_eq!(super::estimate_proof_size(&keys), expected);
+
+    // More keys mean more proof data.
+    let more_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3"), ("A0FF", "4")]);
+    assert!(super::estimate_proof_size(&more_keys) > expected);
+
+    assert_eq!(
+      super::estimate_proof_size::<alloy_primitives::B256, Vec<u8>>(&[]),
+      0
+    );
+  }
+
+  #[test]
+  fn test_branch_for_key_is_ordered_from_root_to_leaf() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, _) = super::build_alloy_trie_with_proof(&keys);
+
+    let key = key_from_nibbles("ABD2");
+    let branch = super::branch_for_key(&keys, &key);
+    // Root branch, branch `A`, branch `AB` and the leaf.
+    assert_eq!(branch.len(), 4);
+    assert_eq!(alloy_primitives::keccak256(&branch[0]), root);
+
+    // Each node is referenced by its parent.
+    for pair in branch.windows(2) {
+      let child_hash = alloy_primitives::keccak256(&pair[1]);
+      assert!(
+        pair[0]
+          .windows(child_hash.len())
+          .any(|window| window == child_hash.as_slice())
+      );
+    }
+
+    let alloy_trie::nodes::TrieNode::Leaf(leaf) =
+      crate::nodes::decode(branch.last().unwrap()).unwrap()
+    else {
+      panic!("expected leaf node");
+    };
+    assert_eq!(leaf.value, b"2");
+  }
+
+  #[test]
+  fn test_removal_witness_is_enough_to_collapse() {
+    let cases: [(&[(&str, &str)], &str); 4] = [
+      // Sibling branch, extension and leaf pulled up into a parent branch.
+      (
+        &[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")],
+        "A0FF",
+      ),
+      (
+        &[
+          ("AB3C1", "1"),
+          ("AB3D2", "2"),
+          ("A0FFF", "3"),
+          ("E9999", "4"),
+        ],
+        "A0FFF",
+      ),
+      (&[("AB1", "1"), ("A0F", "3"), ("E99", "4")], "A0F"),
+      // Sibling leaf merged with a parent extension.
+      (&[("AB1", "1"), ("A0F", "3")], "A0F"),
+    ];
+    for (case, removee) in cases {
+      let mut keys = create_test_data(case);
+      let removee_key = key_from_nibbles(removee);
+      let (old_root, new_root, nodes) = super::removal_witness(&keys, &removee_key);
+
+      // Guest reconstructs the partial trie and collapses it on its own.
+      let mut r0_trie = Trie::from_rlp(&nodes).unwrap();
+      assert_eq!(r0_trie.hash_slow(), old_root);
+      assert!(r0_trie.remove(removee_key));
+      assert_eq!(r0_trie.hash_slow(), new_root);
+
+      keys.retain(|(key, _)| *key != removee_key);
+      assert_eq!(new_root, super::alloy_root_only(&keys));
+    }
+  }
+
+  #[test]
+  fn test_removal_witness_retains_only_path_and_sibling() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, _, nodes) = super::removal_witness(&keys, &key_from_nibbles("A0FF"));
+    // Root branch, branch `A`, the removed leaf and sibling branch `AB` - but not its leaves.
+    assert_eq!(nodes.len(), 4);
+
+    let (old_root, new_root, nodes) = super::removal_witness(&keys, &key_from_nibbles("F000"));
+    assert_eq!(old_root, new_root);
+    assert_eq!(
+      nodes,
+      super::build_exclusion_proof(&keys, &key_from_nibbles("F000"))
+    );
+  }
+
+  #[test]
+  fn test_same_root_ignores_order_but_not_values() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let mut shuffled = keys.clone();
+    shuffled.reverse();
+    shuffled.swap(0, 2);
+    assert!(super::same_root(&keys, &shuffled));
+
+    shuffled[1].1 = b"5".to_vec();
+    assert!(!super::same_root(&keys, &shuffled));
+  }
+
+  #[test]
+  fn test_build_with_progress_reports_every_interval() {
+    let keys = (0..2 * super::PROGRESS_INTERVAL as u64 + 100)
+      .map(|i| {
+        (
+          alloy_primitives::keccak256(i.to_be_bytes()),
+          i.to_be_bytes(),
+        )
+      })
+      .collect::<Vec<_>>();
+    let total = keys.len();
+
+    let mut reports = vec![];
+    let result =
+      super::build_with_progress(&keys, |processed, total| reports.push((processed, total)));
+    assert_eq!(result, super::build_alloy_trie_with_proof(&keys));
+    assert_eq!(
+      reports,
+      vec![
+        (super::PROGRESS_INTERVAL, total),
+        (2 * super::PROGRESS_INTERVAL, total),
+        (total, total),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_build_opts_toggle_sorting_and_proofs() {
+    let mut keys = create_test_data(&[("E999", "3"), ("ABD2", "2"), ("A0FF", "4"), ("ABC1", "1")]);
+    let expected = super::build_alloy_trie_with_proof(&keys);
+    let opts = super::BuildOpts::default();
+    assert_eq!(
+      super::build_alloy_trie_with_proof_opts(&keys, opts),
+      expected
+    );
+
+    let opts = super::BuildOpts {
+      retain_proofs: false,
+      ..opts
+    };
+    assert_eq!(
+      super::build_alloy_trie_with_proof_opts(&keys, opts),
+      (expected.0, vec![])
+    );
+
+    keys.sort();
+    let opts = super::BuildOpts {
+      skip_sort: true,
+      ..opts
+    };
+    assert_eq!(
+      super::build_alloy_trie_with_proof_opts(&keys, opts),
+      expected
+    );
+  }
+
+  #[test]
+  fn test_build_opts_force_hash_children() {
+    // Leaves of short keys and values are 5 bytes long, so by default they are inlined.
+    let keys = vec![
+      (vec![0xA1, 0x11], b"1".to_vec()),
+      (vec![0xB1, 0x11], b"2".to_vec()),
+      (vec![0xC2, 0x22], b"3".to_vec()),
+    ];
+    let (root, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let opts = super::BuildOpts {
+      force_hash_children: true,
+      ..super::BuildOpts::default()
+    };
+    let (forced_root, forced_rlp_nodes) = super::build_alloy_trie_with_proof_opts(&keys, opts);
+    // Both derived by encoding and hashing the nodes with a standalone keccak256 and RLP script,
+    // not alloy-trie - once with leaves inlined into the root branch, once referenced by hash.
+    assert_eq!(
+      root,
+      alloy_primitives::b256!("0xb4431027105121146abe9c177d469381779e846c1c7bcd3a0d0790119d376ebc")
+    );
+    assert_eq!(
+      forced_root,
+      alloy_primitives::b256!("0xc0145ffd0e124211f8e60b95d05d75490033197e6f22e1cb2f6ab6b044b2acca")
+    );
+
+    // Root branch and 3 leaves are retained in both modes.
+    assert_eq!(rlp_nodes.len(), 4);
+    assert_eq!(forced_rlp_nodes.len(), 4);
+    let lengths =
+      |nodes: &[alloy_primitives::Bytes]| nodes.iter().map(|rlp| rlp.len()).collect::<Vec<_>>();
+    assert_eq!(lengths(&rlp_nodes), [30, 5, 5, 5]);
+    assert_eq!(lengths(&forced_rlp_nodes), [115, 5, 5, 5]);
+
+    // Nodes a verifier has to be given, i.e. the root and nodes referenced by hash.
+    let hash_referenced = |root: alloy_primitives::B256, nodes: &[alloy_primitives::Bytes]| {
+      nodes
+        .iter()
+        .map(alloy_primitives::keccak256)
+        .filter(|hash| {
+          *hash == root
+            || nodes
+              .iter()
+              .any(|rlp| rlp.windows(32).any(|window| window == hash.as_slice()))
+        })
+        .count()
+    };
+    // Only the root branch by default, while forced references leave no leaf inlined.
+    assert_eq!(hash_referenced(root, &rlp_nodes), 1);
+    assert_eq!(hash_referenced(forced_root, &forced_rlp_nodes), 4);
+
+    // Each node still hashes to the reference held by its parent.
+    assert_eq!(
+      crate::verify_key_path(forced_root, &forced_rlp_nodes, &[0xB1, 0x11]),
+      Ok(Some(b"2".to_vec()))
+    );
+  }
+
+  #[test]
+  fn test_build_with_encoder_matches_hand_encoded_build() {
+    let keys = [
+      (key_from_nibbles("ABC1"), alloy_primitives::U256::from(1)),
+      (
+        key_from_nibbles("ABD2"),
+        alloy_primitives::U256::from(0x1234),
+      ),
+      (key_from_nibbles("E999"), alloy_primitives::U256::MAX),
+    ];
+    let encoded_keys = vec![
+      (keys[0].0, vec![0x01]),
+      (keys[1].0, vec![0x82, 0x12, 0x34]),
+      (keys[2].0, [&[0xA0][..], &[0xFF; 32]].concat()),
+    ];
+    assert_eq!(
+      super::build_with_encoder(&keys, |value| alloy_rlp::encode(*value)),
+      super::build_alloy_trie_with_proof(&encoded_keys)
+    );
+  }
+
+  #[test]
+  fn test_proof_node_order_stable() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let mut reversed_keys = keys.clone();
+    reversed_keys.reverse();
+
+    let (_, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::build_alloy_trie_with_proof(&keys).1, rlp_nodes);
+    assert_eq!(
+      super::build_alloy_trie_with_proof(&reversed_keys).1,
+      rlp_nodes
+    );
+
+    // Paths of the nodes never decrease, e.g. the root is followed by branch `A`.
+    let (_, nodes) = super::build_alloy_trie_with_proof_ordered(&keys);
+    assert!(nodes.is_sorted_by_key(|(path, _)| *path));
+  }
+
+  #[test]
+  fn test_combined_branch_proof_shares_ancestors() {
+    let keys = create_test_data(&[
+      ("ABC1", "1"),
+      ("ABC2", "2"),
+      ("ABC3", "3"),
+      ("A0FF", "4"),
+      ("E999", "5"),
+    ]);
+    let (root, _) = super::build_alloy_trie_with_proof(&keys);
+    let proof_keys = [
+      key_from_nibbles("ABC1"),
+      key_from_nibbles("ABC2"),
+      key_from_nibbles("ABC3"),
+    ];
+
+    let proof = super::combined_branch_proof(&keys, &proof_keys);
+    let separate_nodes = proof_keys
+      .iter()
+      .map(|key| super::branch_for_key(&keys, key).len())
+      .sum::<usize>();
+    // Root branch, branch `A`, extension `AB` and branch `ABC` are shared by all three leaves.
+    assert_eq!(proof.len(), 4 + 3);
+    assert!(proof.len() < separate_nodes);
+
+    for ((key, value), proof_key) in keys.iter().zip(&proof_keys) {
+      assert_eq!(key, proof_key);
+      assert_eq!(
+        super::verify_key_path(root, &proof, key.as_slice()),
+        Ok(Some(value.clone()))
+      );
+    }
+  }
+
+  #[test]
+  fn test_matches_root_of_known_items() {
+    // Stand-in for a dump with a known root - keys short enough for the whole trie to fit in the
+    // root branch, with each 5-byte leaf inlined.
+    let mut keys = vec![
+      (vec![0xA1, 0x11], b"1".to_vec()),
+      (vec![0xB1, 0x11], b"2".to_vec()),
+      (vec![0xC2, 0x22], b"3".to_vec()),
+    ];
+    let mut root_node = vec![0xDD];
+    root_node.extend([alloy_rlp::EMPTY_STRING_CODE; 10]);
+    // Leaves `111`, `111` and `222` in slots `A`, `B` and `C`, odd-length paths flagged by `0x3`.
+    root_node.extend([0xC4, 0x82, 0x31, 0x11, b'1']);
+    root_node.extend([0xC4, 0x82, 0x31, 0x11, b'2']);
+    root_node.extend([0xC4, 0x82, 0x32, 0x22, b'3']);
+    root_node.extend([alloy_rlp::EMPTY_STRING_CODE; 4]);
+    let expected =
+      alloy_primitives::b256!("0xb4431027105121146abe9c177d469381779e846c1c7bcd3a0d0790119d376ebc");
+    assert_eq!(alloy_primitives::keccak256(&root_node), expected);
+    assert!(super::matches_root(&keys, expected));
+
+    keys[2].1 = b"5".to_vec();
+    assert!(!super::matches_root(&keys, expected));
+  }
+
+  #[test]
+  fn test_build_from_nibbles_matches_byte_keys() {
+    let keys = create_test_data(&[("DA01", "1"), ("EA02", "4"), ("DA02", "2"), ("EA01", "3")]);
+    let paths = keys
+      .iter()
+      .map(|(key, value)| (alloy_trie::Nibbles::unpack(key), value.clone()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      super::build_from_nibbles(&paths),
+      super::build_alloy_trie_with_proof(&keys)
+    );
+  }
+
+  #[test]
+  fn test_common_prefix_nodes() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABC2", "2"), ("AF00", "3"), ("0000", "4")]);
+    let key_a = key_from_nibbles("ABC1");
+    let key_b = key_from_nibbles("ABC2");
+
+    // Root branch, branch `A`, extension `AB` and branch `ABC`.
+    assert_eq!(super::common_prefix_nodes(&keys, &key_a, &key_b), 4);
+    // Only the root branch is shared with a key in another subtrie.
+    assert_eq!(
+      super::common_prefix_nodes(&keys, &key_a, &key_from_nibbles("0000")),
+      1
+    );
+    // Whole proof is shared with the key itself.
+    let proof = super::branch_for_key(&keys, &key_a);
+    assert_eq!(
+      super::common_prefix_nodes(&keys, &key_a, &key_a),
+      proof.len()
+    );
+  }
+
+  #[test]
+  fn test_presorted_build_matches_sorting_build() {
+    let mut keys = create_test_data(&[("E999", "3"), ("ABD2", "2"), ("A0FF", "4"), ("ABC1", "1")]);
+    keys.sort();
+    assert_eq!(
+      super::build_alloy_trie_presorted(&keys),
+      super::build_alloy_trie_with_proof(&keys)
+    );
+  }
+
+  #[cfg(debug_assertions)]
+  #[test]
+  #[should_panic(expected = "items are not sorted by key")]
+  fn test_presorted_build_rejects_unsorted_items_in_debug() {
+    let keys = create_test_data(&[("E999", "3"), ("ABD2", "2")]);
+    super::build_alloy_trie_presorted(&keys);
+  }
+
+  #[test]
+  fn test_proofs_for_keys_verify_against_shared_root() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, _) = super::build_alloy_trie_with_proof(&keys);
+
+    let proof_keys = [key_from_nibbles("ABD2"), key_from_nibbles("E999")];
+    let proofs = super::build_proofs_for_keys(&keys, &proof_keys);
+    assert_eq!(proofs.len(), proof_keys.len());
+
+    for (key, value) in keys.iter().filter(|(k, _)| proof_keys.contains(k)) {
+      let proof = &proofs[key];
+      assert_eq!(proof, &super::branch_for_key(&keys, key));
+      assert_eq!(
+        super::verify_key_path(root, proof, key.as_slice()),
+        Ok(Some(value.clone()))
+      );
+    }
+  }
+
+  #[test]
+  fn test_empty_root_matches_hash_of_empty_string_rlp() {
+    let empty_string_rlp = alloy_rlp::encode(b"".as_slice());
+    assert_eq!(
+      super::EMPTY_ROOT,
+      alloy_primitives::keccak256(empty_string_rlp)
+    );
+    assert!(super::is_empty_root(super::EMPTY_ROOT));
+
+    let keys = create_test_data(&[("ABC1", "1")]);
+    assert!(!super::is_empty_root(super::alloy_root_only(&keys)));
+  }
+
+  #[test]
+  fn test_empty_value_roundtrip() {
+    let keys = create_test_data(&[("ABC1", ""), ("ABD2", "2"), ("E999", "3")]);
+    let (alloy_hash, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    let r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), alloy_hash);
+
+    // Leaf with an empty value is still part of the trie.
+    let keys_without_empty = create_test_data(&[("ABD2", "2"), ("E999", "3")]);
+    assert_ne!(super::alloy_root_only(&keys_without_empty), alloy_hash);
+  }
+
+  #[test]
+  fn test_bounded_build_enforces_byte_budget() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let size = super::estimate_proof_size(&keys);
+
+    assert_eq!(
+      super::build_alloy_trie_with_proof_bounded(&keys, size),
+      Ok(super::build_alloy_trie_with_proof(&keys))
+    );
+    assert_eq!(
+      super::build_alloy_trie_with_proof_bounded(&keys, size - 1),
+      Err(super::ProofTooLarge {
+        size,
+        max_bytes: size - 1
+      })
+    );
+  }
+
+  #[test]
+  fn test_indexed_proof_nodes_hash_their_rlp() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, proof_nodes) = super::build_alloy_trie_with_proof_indexed(&keys);
+    let (ordered_root, ordered_nodes) = super::build_alloy_trie_with_proof_ordered(&keys);
+    assert_eq!(root, ordered_root);
+    assert_eq!(proof_nodes.len(), ordered_nodes.len());
+
+    for (node, (path, rlp)) in proof_nodes.iter().zip(&ordered_nodes) {
+      assert_eq!(node.hash, alloy_primitives::keccak256(&node.rlp));
+      assert_eq!((&node.path, &node.rlp), (path, rlp));
+    }
+    assert_eq!(proof_nodes[0].hash, root);
+    assert!(proof_nodes[0].path.is_empty());
+  }
+
+  #[test]
+  fn test_single_key_trie() {
+    let keys = create_test_data(&[("ABC1", "1")]);
+    let (alloy_hash, rlp_nodes) = super::build_alloy_trie_with_proof(&keys);
+    assert_eq!(rlp_nodes.len(), 1);
+
+    let (key, value) = &keys[0];
+    let leaf = alloy_trie::nodes::LeafNode::new(alloy_trie::Nibbles::unpack(key), value.clone());
+    let leaf_rlp = alloy_rlp::encode(&leaf);
+    assert_eq!(rlp_nodes[0].as_ref(), leaf_rlp.as_slice());
+    assert_eq!(alloy_hash, alloy_primitives::keccak256(&leaf_rlp));
+
+    let mut r0_trie = Trie::from_rlp(rlp_nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), alloy_hash);
+    assert!(r0_trie.remove(key));
+    assert_eq!(r0_trie.hash_slow(), super::EMPTY_ROOT);
+  }
+
+  #[test]
+  fn test_build_from_map_is_deterministic() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let map = keys
+      .iter()
+      .cloned()
+      .collect::<alloy_primitives::map::HashMap<_, _>>();
+    let reversed_map = keys
+      .iter()
+      .rev()
+      .cloned()
+      .collect::<alloy_primitives::map::HashMap<_, _>>();
+
+    let output = super::build_from_map(&map);
+    assert_eq!(super::build_from_map(&map), output);
+    assert_eq!(super::build_from_map(&reversed_map), output);
+    assert_eq!(output, super::build_alloy_trie_with_proof(&keys));
+  }
+
+  #[test]
+  fn test_update_root_on_insert_matches_full_rebuild() {
+    let old_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let new_key = key_from_nibbles("A0FF");
+    let new_root = super::update_root_on_insert(&old_keys, new_key, b"4".to_vec());
+
+    let all_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3"), ("A0FF", "4")]);
+    assert_eq!(new_root, super::alloy_root_only(&all_keys));
+
+    // Inserting an existing key replaces its value.
+    let updated_root =
+      super::update_root_on_insert(&all_keys, key_from_nibbles("E999"), b"5".to_vec());
+    let updated_keys =
+      create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "5"), ("A0FF", "4")]);
+    assert_eq!(updated_root, super::alloy_root_only(&updated_keys));
+  }
 }
@@ -0,0 +1,173 @@
+//! Computes Alloy and Risc0 roots for key-value pairs read from a file.
+//!
+//! Usage: `risc0-mpt-collapse --input <path> [--format json|csv]`
+//!
+//! JSON input is an array of `{"key": "0x..", "value": "0x.."}` objects, CSV input has one
+//! `key,value` pair of hex strings per line. Exits with non-zero status if the roots disagree.
+
+use std::process::ExitCode;
+
+/// Format of the input file.
+enum Format {
+  Json,
+  Csv,
+}
+
+/// Parsed command-line arguments.
+struct Args {
+  input: std::path::PathBuf,
+  format: Format,
+}
+
+/// Errors of the command-line tool, with the context they occurred in.
+#[derive(Debug)]
+enum CliError {
+  /// Invalid command-line arguments.
+  Args(String),
+  /// Input file could not be read.
+  Read {
+    path: std::path::PathBuf,
+    source: std::io::Error,
+  },
+  /// Input file content is malformed.
+  Input(String),
+  /// Items cannot form a valid trie.
+  Build(risc0_mpt_collapse::TrieBuildError),
+  /// Alloy proof nodes could not be decoded into a Risc0 trie.
+  Reconstruct(alloy_rlp::Error),
+}
+
+impl std::fmt::Display for CliError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Args(msg) => write!(f, "{msg}"),
+      Self::Read { path, .. } => write!(f, "cannot read {}", path.display()),
+      Self::Input(msg) => write!(f, "invalid input: {msg}"),
+      Self::Build(_) => write!(f, "cannot build Alloy trie"),
+      Self::Reconstruct(_) => write!(f, "cannot reconstruct Risc0 trie"),
+    }
+  }
+}
+
+impl std::error::Error for CliError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Args(_) | Self::Input(_) => None,
+      Self::Read { source, .. } => Some(source),
+      Self::Build(err) => Some(err),
+      Self::Reconstruct(err) => Some(err),
+    }
+  }
+}
+
+fn main() -> ExitCode {
+  match run() {
+    Ok(true) => ExitCode::SUCCESS,
+    Ok(false) => {
+      eprintln!("Error: Alloy and Risc0 roots disagree");
+      ExitCode::FAILURE
+    }
+    Err(err) => {
+      eprintln!("Error: {err}");
+      let mut source = std::error::Error::source(&err);
+      while let Some(cause) = source {
+        eprintln!("Caused by: {cause}");
+        source = cause.source();
+      }
+      ExitCode::from(2)
+    }
+  }
+}
+
+/// Builds both tries and prints their roots, returning whether the roots agree.
+fn run() -> Result<bool, CliError> {
+  let args = parse_args(std::env::args().skip(1)).map_err(CliError::Args)?;
+  let content = std::fs::read_to_string(&args.input).map_err(|source| CliError::Read {
+    path: args.input.clone(),
+    source,
+  })?;
+  let items = match args.format {
+    Format::Json => parse_json(&content),
+    Format::Csv => parse_csv(&content),
+  }
+  .map_err(CliError::Input)?;
+
+  let (alloy_root, rlp_nodes) =
+    risc0_mpt_collapse::try_build_alloy_trie_with_proof(&items).map_err(CliError::Build)?;
+  let r0_trie = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes).map_err(CliError::Reconstruct)?;
+  let r0_root = r0_trie.hash_slow();
+
+  println!("Alloy root: {alloy_root}");
+  println!("Risc0 root: {r0_root}");
+  // Both counts agree when no node is inlined into its parent (see `test_node_count_matches`),
+  // otherwise they may differ - which alone does not signal a mismatch.
+  println!("Proof nodes: {}", rlp_nodes.len());
+  println!("Risc0 trie size: {}", r0_trie.size());
+
+  Ok(alloy_root == r0_root)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+  let mut input = None;
+  let mut format = None;
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--input" => input = Some(args.next().ok_or("missing value for --input")?),
+      "--format" => {
+        format = match args.next().as_deref() {
+          Some("json") => Some(Format::Json),
+          Some("csv") => Some(Format::Csv),
+          Some(other) => return Err(format!("unknown format {other}, expected json or csv")),
+          None => return Err("missing value for --format".to_string()),
+        }
+      }
+      other => return Err(format!("unknown argument {other}")),
+    }
+  }
+
+  let input = std::path::PathBuf::from(input.ok_or("missing --input <path>")?);
+  // Fall back to the file extension when format is not given explicitly.
+  let format = format.unwrap_or_else(|| match input.extension() {
+    Some(ext) if ext == "csv" => Format::Csv,
+    _ => Format::Json,
+  });
+
+  Ok(Args { input, format })
+}
+
+fn parse_json(content: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>, String> {
+  let json: serde_json::Value =
+    serde_json::from_str(content).map_err(|err| format!("malformed JSON: {err}"))?;
+  let entries = json.as_array().ok_or("expected a JSON array of entries")?;
+  entries
+    .iter()
+    .enumerate()
+    .map(|(i, entry)| {
+      let field = |name: &str| {
+        entry
+          .get(name)
+          .and_then(serde_json::Value::as_str)
+          .ok_or_else(|| format!("entry {i}: missing string field {name}"))
+      };
+      Ok((parse_hex(field("key")?)?, parse_hex(field("value")?)?))
+    })
+    .collect()
+}
+
+fn parse_csv(content: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>, String> {
+  content
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty())
+    .map(|(i, line)| {
+      let (key, value) = line
+        .split_once(',')
+        .ok_or_else(|| format!("line {}: expected key,value", i + 1))?;
+      Ok((parse_hex(key.trim())?, parse_hex(value.trim())?))
+    })
+    .collect()
+}
+
+fn parse_hex(field: &str) -> Result<Vec<u8>, String> {
+  alloy_primitives::hex::decode(field).map_err(|err| format!("invalid hex {field}: {err}"))
+}
@@ -0,0 +1,266 @@
+//! Decoding and traversal of RLP-encoded trie nodes, independent of the Risc0 trie.
+
+use alloc::{vec, vec::Vec};
+use alloy_rlp::Decodable;
+
+/// Errors returned while resolving proof nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum NodeError {
+  /// Node could not be decoded.
+  Rlp(alloy_rlp::Error),
+  /// Node referenced by hash is not among the proof nodes.
+  MissingNode(alloy_primitives::B256),
+}
+
+/// Proof nodes indexed by their keccak256 hash.
+pub(crate) struct NodeIndex<'a> {
+  nodes: alloy_primitives::map::B256Map<&'a [u8]>,
+}
+
+impl<'a> NodeIndex<'a> {
+  pub(crate) fn new(rlp_nodes: &'a [alloy_primitives::Bytes]) -> Self {
+    Self::with_hasher::<crate::KeccakHasher>(rlp_nodes)
+  }
+
+  /// Indexes nodes by hashes computed with given hasher.
+  pub(crate) fn with_hasher<H: crate::TrieHasher>(
+    rlp_nodes: &'a [alloy_primitives::Bytes],
+  ) -> Self {
+    let nodes = rlp_nodes
+      .iter()
+      .map(|rlp| (H::hash(rlp), rlp.as_ref()))
+      .collect();
+    Self { nodes }
+  }
+
+  /// Looks up the RLP of the node with given hash.
+  pub(crate) fn rlp_by_hash(&self, hash: alloy_primitives::B256) -> Result<&'a [u8], NodeError> {
+    self
+      .nodes
+      .get(&hash)
+      .copied()
+      .ok_or(NodeError::MissingNode(hash))
+  }
+
+  /// Looks up and decodes the node with given hash.
+  pub(crate) fn node_by_hash(
+    &self,
+    hash: alloy_primitives::B256,
+  ) -> Result<alloy_trie::nodes::TrieNode, NodeError> {
+    decode(self.rlp_by_hash(hash)?)
+  }
+
+  /// Decodes the child node, either looking it up by hash or decoding it inline.
+  pub(crate) fn resolve(
+    &self,
+    child: &alloy_trie::nodes::RlpNode,
+  ) -> Result<alloy_trie::nodes::TrieNode, NodeError> {
+    match child.as_hash() {
+      Some(hash) => self.node_by_hash(hash),
+      None => decode(child),
+    }
+  }
+}
+
+/// Decodes a single RLP-encoded trie node.
+pub(crate) fn decode(mut rlp: &[u8]) -> Result<alloy_trie::nodes::TrieNode, NodeError> {
+  alloy_trie::nodes::TrieNode::decode(&mut rlp).map_err(NodeError::Rlp)
+}
+
+/// Returns the child of a branch at given nibble, if the slot is occupied.
+pub(crate) fn branch_child(
+  branch: &alloy_trie::nodes::BranchNode,
+  nibble: u8,
+) -> Option<&alloy_trie::nodes::RlpNode> {
+  if !branch.state_mask.is_bit_set(nibble) {
+    return None;
+  }
+  // Stack holds occupied slots only, so the position is the number of occupied slots before.
+  let position = (branch.state_mask.get() & ((1u16 << nibble) - 1)).count_ones() as usize;
+  branch.stack.get(position)
+}
+
+/// Collects all leaves reachable from the root node (first of `rlp_nodes`), with their full paths.
+pub(crate) fn collect_leaves(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<(alloy_trie::Nibbles, Vec<u8>)>, NodeError> {
+  let Some(root) = rlp_nodes.first() else {
+    return Ok(Vec::new());
+  };
+  let index = NodeIndex::new(rlp_nodes);
+  let mut leaves = Vec::new();
+  collect_leaves_from(
+    &index,
+    decode(root)?,
+    alloy_trie::Nibbles::default(),
+    &mut leaves,
+  )?;
+  Ok(leaves)
+}
+
+/// Depth-first walk in nibble order, so leaves are collected sorted by their paths.
+fn collect_leaves_from(
+  index: &NodeIndex<'_>,
+  node: alloy_trie::nodes::TrieNode,
+  path: alloy_trie::Nibbles,
+  leaves: &mut Vec<(alloy_trie::Nibbles, Vec<u8>)>,
+) -> Result<(), NodeError> {
+  match node {
+    alloy_trie::nodes::TrieNode::EmptyRoot => {}
+    alloy_trie::nodes::TrieNode::Branch(branch) => {
+      for nibble in 0..16 {
+        if let Some(child) = branch_child(&branch, nibble) {
+          let mut child_path = path;
+          child_path.push(nibble);
+          collect_leaves_from(index, index.resolve(child)?, child_path, leaves)?;
+        }
+      }
+    }
+    alloy_trie::nodes::TrieNode::Extension(extension) => {
+      let child = index.resolve(&extension.child)?;
+      collect_leaves_from(index, child, path.join(&extension.key), leaves)?;
+    }
+    alloy_trie::nodes::TrieNode::Leaf(leaf) => leaves.push((path.join(&leaf.key), leaf.value)),
+  }
+  Ok(())
+}
+
+/// Collects nodes referenced by hash along `path`, starting at the root node (first of
+/// `rlp_nodes`) and stopping where the path ends or diverges from the trie.
+///
+/// Inlined nodes are part of their parents, so they are not collected separately.
+#[cfg(feature = "std")]
+pub(crate) fn path_nodes(
+  rlp_nodes: &[alloy_primitives::Bytes],
+  path: &alloy_trie::Nibbles,
+) -> Result<Vec<alloy_primitives::Bytes>, NodeError> {
+  let Some(root) = rlp_nodes.first() else {
+    return Ok(Vec::new());
+  };
+  let index = NodeIndex::new(rlp_nodes);
+  let mut proof = vec![root.clone()];
+  let mut depth = 0;
+  let mut node = decode(root)?;
+  loop {
+    let child = match &node {
+      alloy_trie::nodes::TrieNode::EmptyRoot | alloy_trie::nodes::TrieNode::Leaf(_) => break,
+      alloy_trie::nodes::TrieNode::Extension(extension) => {
+        if !path.slice(depth..).starts_with(&extension.key) {
+          break;
+        }
+        depth += extension.key.len();
+        extension.child.clone()
+      }
+      alloy_trie::nodes::TrieNode::Branch(branch) => {
+        let Some(child) = path
+          .get(depth)
+          .and_then(|nibble| branch_child(branch, nibble))
+        else {
+          break;
+        };
+        depth += 1;
+        child.clone()
+      }
+    };
+    node = match child.as_hash() {
+      Some(hash) => {
+        let rlp = index.rlp_by_hash(hash)?;
+        proof.push(alloy_primitives::Bytes::copy_from_slice(rlp));
+        decode(rlp)?
+      }
+      None => decode(&child)?,
+    };
+  }
+  Ok(proof)
+}
+
+/// Collects hashes of nodes referenced from the root node (first of `rlp_nodes`), but missing
+/// from `rlp_nodes`, in depth-first nibble order.
+pub(crate) fn collect_unresolved(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<alloy_primitives::B256>, NodeError> {
+  let Some(root) = rlp_nodes.first() else {
+    return Ok(Vec::new());
+  };
+  let index = NodeIndex::new(rlp_nodes);
+  let mut unresolved = Vec::new();
+  collect_unresolved_from(&index, decode(root)?, &mut unresolved)?;
+  Ok(unresolved)
+}
+
+fn collect_unresolved_from(
+  index: &NodeIndex<'_>,
+  node: alloy_trie::nodes::TrieNode,
+  unresolved: &mut Vec<alloy_primitives::B256>,
+) -> Result<(), NodeError> {
+  let children = match node {
+    alloy_trie::nodes::TrieNode::Branch(branch) => branch.stack,
+    alloy_trie::nodes::TrieNode::Extension(extension) => vec![extension.child],
+    alloy_trie::nodes::TrieNode::EmptyRoot | alloy_trie::nodes::TrieNode::Leaf(_) => return Ok(()),
+  };
+  for child in children {
+    let child_node = match child.as_hash() {
+      Some(hash) => match index.rlp_by_hash(hash) {
+        Ok(rlp) => decode(rlp)?,
+        Err(_) => {
+          unresolved.push(hash);
+          continue;
+        }
+      },
+      None => decode(&child)?,
+    };
+    collect_unresolved_from(index, child_node, unresolved)?;
+  }
+  Ok(())
+}
+
+/// Collects paths and hashes of nodes referenced by hash from the root node (first of
+/// `rlp_nodes`), including the root itself. References to missing nodes are skipped.
+pub(crate) fn collect_node_paths(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<(alloy_trie::Nibbles, alloy_primitives::B256)>, NodeError> {
+  let Some(root) = rlp_nodes.first() else {
+    return Ok(Vec::new());
+  };
+  let index = NodeIndex::new(rlp_nodes);
+  let root_path = alloy_trie::Nibbles::default();
+  let mut node_paths = vec![(root_path, alloy_primitives::keccak256(root))];
+  collect_node_paths_from(&index, decode(root)?, root_path, &mut node_paths)?;
+  Ok(node_paths)
+}
+
+fn collect_node_paths_from(
+  index: &NodeIndex<'_>,
+  node: alloy_trie::nodes::TrieNode,
+  path: alloy_trie::Nibbles,
+  node_paths: &mut Vec<(alloy_trie::Nibbles, alloy_primitives::B256)>,
+) -> Result<(), NodeError> {
+  let children = match node {
+    alloy_trie::nodes::TrieNode::Branch(branch) => (0..16)
+      .filter_map(|nibble| {
+        let child = branch_child(&branch, nibble)?.clone();
+        let mut child_path = path;
+        child_path.push(nibble);
+        Some((child_path, child))
+      })
+      .collect(),
+    alloy_trie::nodes::TrieNode::Extension(extension) => {
+      vec![(path.join(&extension.key), extension.child)]
+    }
+    alloy_trie::nodes::TrieNode::EmptyRoot | alloy_trie::nodes::TrieNode::Leaf(_) => Vec::new(),
+  };
+  for (child_path, child) in children {
+    let child_node = match child.as_hash() {
+      Some(hash) => match index.rlp_by_hash(hash) {
+        Ok(rlp) => {
+          node_paths.push((child_path, hash));
+          decode(rlp)?
+        }
+        Err(_) => continue,
+      },
+      None => decode(&child)?,
+    };
+    collect_node_paths_from(index, child_node, child_path, node_paths)?;
+  }
+  Ok(())
+}
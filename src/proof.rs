@@ -0,0 +1,597 @@
+use alloc::vec::Vec;
+
+/// Root hash and RLP-encoded proof nodes, in a form that can be persisted
+///
+/// With the `serde` feature enabled, the root and nodes serialize as hex strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SerializableProof {
+  /// Root hash of the trie.
+  pub root: alloy_primitives::B256,
+  /// RLP-encoded proof nodes, root node first.
+  pub nodes: Vec<alloy_primitives::Bytes>,
+}
+
+impl SerializableProof {
+  /// Wraps the output of [`crate::build_alloy_trie_with_proof`].
+  pub fn from_build_output(
+    (root, nodes): (alloy_primitives::B256, Vec<alloy_primitives::Bytes>),
+  ) -> Self {
+    Self { root, nodes }
+  }
+
+  /// Unwraps into the same shape as the output of [`crate::build_alloy_trie_with_proof`].
+  pub fn into_build_output(self) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+    (self.root, self.nodes)
+  }
+}
+
+/// Converts a proof returned by `eth_getProof` into RLP-encoded nodes
+///
+/// Both `accountProof` and each `storageProof[].proof` are arrays of hex strings, root node first,
+/// which can be passed here directly. The result can be fed into Risc0 `Trie::from_rlp`.
+///
+/// # Arguments
+/// * `proof_hex` - Hex-encoded proof nodes, with or without `0x` prefix
+///
+/// # Returns
+/// * RLP-encoded proof nodes, or the first hex decoding error
+pub fn from_eth_proof<S: AsRef<str>>(
+  proof_hex: &[S],
+) -> Result<Vec<alloy_primitives::Bytes>, alloy_primitives::hex::FromHexError> {
+  proof_hex
+    .iter()
+    .map(|node| alloy_primitives::hex::decode(node.as_ref()).map(alloy_primitives::Bytes::from))
+    .collect()
+}
+
+/// Checks that proof nodes form the complete trie with given root
+///
+/// Guards the build functions retaining proofs for all keys - the nodes must reconstruct into a
+/// Risc0 trie with the expected root, and no child may be left referenced by hash only.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `rlp_nodes` - RLP-encoded trie nodes, root node first
+///
+/// # Returns
+/// * `Ok(())` if the trie is complete, [`crate::ProofError::RootMismatch`] if nodes form another
+///   trie, or [`crate::ProofError::MissingNode`] with the first unresolved node
+pub fn assert_complete_proof(
+  root: alloy_primitives::B256,
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<(), crate::ProofError> {
+  let trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).map_err(crate::ProofError::Rlp)?;
+  let actual = trie.hash_slow();
+  if actual != root {
+    return Err(crate::ProofError::RootMismatch {
+      expected: root,
+      actual,
+    });
+  }
+
+  match unresolved_nodes(rlp_nodes)?.first() {
+    Some(&hash) => Err(crate::ProofError::MissingNode(hash)),
+    None => Ok(()),
+  }
+}
+
+/// Merges two partial proofs of the same trie into a single set of nodes
+///
+/// Nodes are deduplicated by hash, so the combined set reconstructs a trie covering the keys of
+/// both proofs. Nodes are located by walking each proof from its root - proofs of different tries
+/// conflict at least at the root path.
+///
+/// # Arguments
+/// * `a` - RLP-encoded proof nodes, root node first
+/// * `b` - RLP-encoded proof nodes, root node first
+///
+/// # Returns
+/// * Nodes of both proofs, root node first, or [`crate::ProofError::ConflictingNodes`] if the
+///   proofs hold different nodes at the same path
+pub fn merge_proofs(
+  a: Vec<alloy_primitives::Bytes>,
+  b: Vec<alloy_primitives::Bytes>,
+) -> Result<Vec<alloy_primitives::Bytes>, crate::ProofError> {
+  let node_paths = crate::nodes::collect_node_paths(&a)?
+    .into_iter()
+    .collect::<alloy_primitives::map::HashMap<_, _>>();
+  for (path, second) in crate::nodes::collect_node_paths(&b)? {
+    if let Some(&first) = node_paths.get(&path)
+      && first != second
+    {
+      return Err(crate::ProofError::ConflictingNodes {
+        path,
+        first,
+        second,
+      });
+    }
+  }
+
+  let mut seen = alloy_primitives::map::HashSet::default();
+  Ok(
+    a.into_iter()
+      .chain(b)
+      .filter(|rlp| seen.insert(alloy_primitives::keccak256(rlp)))
+      .collect(),
+  )
+}
+
+/// Parses key-value pairs from strings, e.g. pasted from logs
+///
+/// # Arguments
+/// * `pairs` - Keys as 32-byte hex strings (with or without `0x` prefix), and values as hex
+///   strings with `0x` prefix or as plain UTF-8 text otherwise
+///
+/// # Returns
+/// * Items ready to build a trie from, or the first hex decoding error
+pub fn items_from_hex(
+  pairs: &[(&str, &str)],
+) -> Result<Vec<(alloy_primitives::B256, Vec<u8>)>, alloy_primitives::hex::FromHexError> {
+  pairs
+    .iter()
+    .map(|(key, value)| {
+      let key = key.parse::<alloy_primitives::B256>()?;
+      let value = match value.strip_prefix("0x") {
+        Some(hex) => alloy_primitives::hex::decode(hex)?,
+        None => value.as_bytes().to_vec(),
+      };
+      Ok((key, value))
+    })
+    .collect()
+}
+
+/// Errors returned when a proof blob cannot be split back into nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofBlobError {
+  /// Blob ends in the middle of a length prefix or a node, which starts at given offset.
+  Truncated { offset: usize },
+}
+
+impl core::fmt::Display for ProofBlobError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Truncated { offset } => write!(f, "proof blob truncated at offset {offset}"),
+    }
+  }
+}
+
+impl core::error::Error for ProofBlobError {}
+
+/// Concatenates proof nodes into a single blob, each prefixed by its length
+///
+/// Length is encoded as 4-byte big-endian integer, so the blob can be split without any knowledge
+/// of RLP. Use [`decode_proof_blob`] to get the nodes back.
+///
+/// # Arguments
+/// * `nodes` - RLP-encoded proof nodes
+///
+/// # Returns
+/// * Length-prefixed nodes, in the same order
+///
+/// # Panics
+/// * If a node is longer than `u32::MAX` bytes
+pub fn encode_proof_blob(nodes: &[alloy_primitives::Bytes]) -> Vec<u8> {
+  let mut blob = Vec::with_capacity(nodes.iter().map(|node| 4 + node.len()).sum());
+  for node in nodes {
+    let len = u32::try_from(node.len()).expect("node length fits in u32");
+    blob.extend_from_slice(&len.to_be_bytes());
+    blob.extend_from_slice(node);
+  }
+  blob
+}
+
+/// Splits a blob created by [`encode_proof_blob`] back into proof nodes
+///
+/// # Arguments
+/// * `blob` - Length-prefixed nodes
+///
+/// # Returns
+/// * Proof nodes, in the same order, or an error if the blob is truncated
+pub fn decode_proof_blob(mut blob: &[u8]) -> Result<Vec<alloy_primitives::Bytes>, ProofBlobError> {
+  let total_len = blob.len();
+  let mut nodes = Vec::new();
+  while !blob.is_empty() {
+    let offset = total_len - blob.len();
+    let truncated = ProofBlobError::Truncated { offset };
+
+    let (len, rest) = blob.split_first_chunk::<4>().ok_or(truncated.clone())?;
+    let len = u32::from_be_bytes(*len) as usize;
+    let (node, rest) = rest.split_at_checked(len).ok_or(truncated)?;
+    nodes.push(alloy_primitives::Bytes::copy_from_slice(node));
+    blob = rest;
+  }
+  Ok(nodes)
+}
+
+/// Error returned when a node given to [`from_rlp_checked`] cannot be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RlpError {
+  /// Index of the offending node.
+  pub index: usize,
+  /// Reason of the decoding failure.
+  pub error: alloy_rlp::Error,
+}
+
+impl core::fmt::Display for RlpError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "invalid node at index {}: {}", self.index, self.error)
+  }
+}
+
+impl core::error::Error for RlpError {
+  fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+    Some(&self.error)
+  }
+}
+
+/// Reconstructs a Risc0 trie, reporting which node fails to decode
+///
+/// Risc0 `Trie::from_rlp` does not tell which node is malformed, so on failure the shortest
+/// failing prefix of `rlp_nodes` is searched for - its last node is the offending one. This only
+/// costs extra work when decoding fails.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the trie, root node first
+///
+/// # Returns
+/// * Reconstructed trie, or the index of the first malformed node along with the reason
+pub fn from_rlp_checked(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<risc0_ethereum_trie::Trie, RlpError> {
+  risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).map_err(|error| {
+    (1..=rlp_nodes.len())
+      .find_map(|len| {
+        let error = risc0_ethereum_trie::Trie::from_rlp(&rlp_nodes[..len]).err()?;
+        Some(RlpError {
+          index: len - 1,
+          error,
+        })
+      })
+      .unwrap_or(RlpError { index: 0, error })
+  })
+}
+
+/// Enumerates all leaves of the trie formed by given nodes
+///
+/// Risc0 trie does not expose its leaves, so the nodes are walked directly, starting at the root
+/// node (first of `rlp_nodes`). Leaves are yielded in ascending order of their paths, so the
+/// original items can be recovered by packing each path back into a key.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the whole trie, root node first
+///
+/// # Returns
+/// * Iterator over full leaf paths and their values, or an error if any node cannot be decoded or
+///   a referenced node is missing
+pub fn iter_leaves(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<impl Iterator<Item = (alloy_trie::Nibbles, Vec<u8>)>, crate::ProofError> {
+  let leaves = crate::nodes::collect_leaves(rlp_nodes)?;
+  Ok(leaves.into_iter())
+}
+
+/// Reconstructs a Risc0 trie, and collects values of all its leaves
+///
+/// Risc0 trie does not expose its leaves, so the nodes are decoded twice - once by
+/// `Trie::from_rlp`, and once more by a separate walk collecting the leaves. Callers get both the
+/// trie and its contents without traversing the nodes themselves, but not in a single pass.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the whole trie, root node first
+///
+/// # Returns
+/// * Reconstructed trie and values keyed by full leaf paths, or an error if any node cannot be
+///   decoded or a referenced node is missing
+pub fn reconstruct_with_values(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<
+  (
+    risc0_ethereum_trie::Trie,
+    alloy_primitives::map::HashMap<alloy_trie::Nibbles, Vec<u8>>,
+  ),
+  crate::ProofError,
+> {
+  let trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).map_err(crate::ProofError::Rlp)?;
+  let values = crate::nodes::collect_leaves(rlp_nodes)?
+    .into_iter()
+    .collect();
+  Ok((trie, values))
+}
+
+/// Recovers the items of the trie formed by given nodes
+///
+/// Same as [`iter_leaves`], but each full leaf path is packed back into a key. Paths of byte keys
+/// always have even length, so keys are reproduced exactly - e.g. 32-byte storage keys.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the whole trie, root node first
+///
+/// # Returns
+/// * Key-value pairs in ascending key order, or an error if any node cannot be decoded or a
+///   referenced node is missing
+pub fn items_from_proof(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<(Vec<u8>, Vec<u8>)>, crate::ProofError> {
+  let leaves = crate::nodes::collect_leaves(rlp_nodes)?;
+  Ok(
+    leaves
+      .into_iter()
+      .map(|(path, value)| (path.pack().to_vec(), value))
+      .collect(),
+  )
+}
+
+/// Lists nodes referenced by hash, but missing from given nodes
+///
+/// Trie reconstructed from a partial proof, e.g. by Risc0 `Trie::from_rlp`, holds such children as
+/// bare digests - any operation reaching them, like a removal that needs to pull one up, fails.
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded nodes of the trie, root node first
+///
+/// # Returns
+/// * Hashes of unresolved nodes in nibble order, empty for a complete trie, or an error if any
+///   node cannot be decoded
+pub fn unresolved_nodes(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<alloy_primitives::B256>, crate::ProofError> {
+  Ok(crate::nodes::collect_unresolved(rlp_nodes)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::create_test_data;
+
+  #[test]
+  fn test_from_eth_proof_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    // Same shape as `accountProof` in `eth_getProof` response.
+    let proof_hex = rlp_nodes
+      .iter()
+      .map(|node| node.to_string())
+      .collect::<Vec<_>>();
+    let decoded = super::from_eth_proof(&proof_hex).unwrap();
+    assert_eq!(decoded, rlp_nodes);
+
+    let r0_trie = risc0_ethereum_trie::Trie::from_rlp(decoded).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+  }
+
+  #[test]
+  fn test_from_eth_proof_rejects_malformed_hex() {
+    assert!(super::from_eth_proof(&["0xf8", "0xzz"]).is_err());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serializable_proof_json_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let proof =
+      super::SerializableProof::from_build_output(crate::build_alloy_trie_with_proof(&keys));
+
+    let json = serde_json::to_string(&proof).unwrap();
+    assert!(json.contains(&proof.root.to_string()));
+
+    let deserialized: super::SerializableProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, proof);
+
+    let (root, nodes) = deserialized.into_build_output();
+    let r0_trie = risc0_ethereum_trie::Trie::from_rlp(nodes).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+  }
+
+  #[test]
+  fn test_iter_leaves_recovers_items() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let recovered = super::iter_leaves(&rlp_nodes)
+      .unwrap()
+      .map(|(path, value)| (alloy_primitives::B256::from_slice(&path.pack()), value))
+      .collect::<Vec<_>>();
+    let mut expected = keys.clone();
+    expected.sort();
+    assert_eq!(recovered, expected);
+
+    assert_eq!(super::iter_leaves(&[]).unwrap().count(), 0);
+  }
+
+  #[test]
+  fn test_iter_leaves_reports_missing_node() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let missing = rlp_nodes.last().unwrap();
+
+    let result = super::iter_leaves(&rlp_nodes[..rlp_nodes.len() - 1]).map(|leaves| leaves.count());
+    assert_eq!(
+      result,
+      Err(crate::ProofError::MissingNode(alloy_primitives::keccak256(
+        missing
+      )))
+    );
+  }
+
+  #[test]
+  fn test_unresolved_nodes_of_selective_proof() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::unresolved_nodes(&rlp_nodes), Ok(vec![]));
+
+    // Proof of `A0FF` leaves sibling branch `AB` and leaf `E999` unresolved.
+    let removee_key = crate::tests::key_from_nibbles("A0FF");
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_selective_proof(&keys, &[removee_key]);
+    let branch_ab = &crate::branch_for_key(&keys, &keys[0].0)[2];
+    let leaf_e999 = crate::branch_for_key(&keys, &keys[3].0).pop().unwrap();
+    assert_eq!(
+      super::unresolved_nodes(&rlp_nodes),
+      Ok(vec![
+        alloy_primitives::keccak256(branch_ab),
+        alloy_primitives::keccak256(leaf_e999)
+      ])
+    );
+  }
+
+  #[test]
+  fn test_items_from_hex_parses_keys_and_values() {
+    let key = "0xabc1000000000000000000000000000000000000000000000000000000000000";
+    let items = super::items_from_hex(&[(key, "0x01ff"), (&key[2..], "hello")]).unwrap();
+    assert_eq!(
+      items,
+      vec![
+        (crate::tests::key_from_nibbles("ABC1"), vec![0x01, 0xff]),
+        (crate::tests::key_from_nibbles("ABC1"), b"hello".to_vec()),
+      ]
+    );
+
+    assert!(super::items_from_hex(&[("0xabc1", "1")]).is_err());
+    assert!(super::items_from_hex(&[(key, "0xzz")]).is_err());
+  }
+
+  #[test]
+  fn test_merge_proofs_covers_both_key_groups() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, _) = crate::build_alloy_trie_with_proof(&keys);
+    let (group_a, group_b) = keys.split_at(2);
+
+    let group_keys = |group: &[(alloy_primitives::B256, Vec<u8>)]| {
+      group.iter().map(|(k, _)| *k).collect::<Vec<_>>()
+    };
+    let (_, proof_a) = crate::build_alloy_trie_with_selective_proof(&keys, &group_keys(group_a));
+    let (_, proof_b) = crate::build_alloy_trie_with_selective_proof(&keys, &group_keys(group_b));
+
+    let merged = super::merge_proofs(proof_a.clone(), proof_b.clone()).unwrap();
+    assert!(merged.len() < proof_a.len() + proof_b.len());
+    for (key, value) in &keys {
+      assert_eq!(
+        crate::verify_key_path(root, &merged, key.as_slice()),
+        Ok(Some(value.clone()))
+      );
+    }
+    let r0_trie = risc0_ethereum_trie::Trie::from_rlp(&merged).unwrap();
+    assert_eq!(r0_trie.hash_slow(), root);
+  }
+
+  #[test]
+  fn test_merge_proofs_rejects_conflicting_nodes() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let other_keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "4")]);
+    let (root, proof) = crate::build_alloy_trie_with_proof(&keys);
+    let (other_root, other_proof) = crate::build_alloy_trie_with_proof(&other_keys);
+
+    assert_eq!(
+      super::merge_proofs(proof, other_proof),
+      Err(crate::ProofError::ConflictingNodes {
+        path: alloy_trie::Nibbles::default(),
+        first: root,
+        second: other_root,
+      })
+    );
+  }
+
+  #[test]
+  fn test_assert_complete_proof() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(super::assert_complete_proof(root, &rlp_nodes), Ok(()));
+
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_selective_proof(
+      &keys,
+      &[crate::tests::key_from_nibbles("E999")],
+    );
+    assert!(matches!(
+      super::assert_complete_proof(root, &rlp_nodes),
+      Err(crate::ProofError::MissingNode(_))
+    ));
+
+    let (other_root, _) = crate::build_alloy_trie_with_proof(&keys[..2]);
+    assert_eq!(
+      super::assert_complete_proof(other_root, &rlp_nodes),
+      Err(crate::ProofError::RootMismatch {
+        expected: other_root,
+        actual: root,
+      })
+    );
+
+    let empty_nodes: [alloy_primitives::Bytes; 0] = [];
+    assert_eq!(
+      super::assert_complete_proof(crate::EMPTY_ROOT, &empty_nodes),
+      Ok(())
+    );
+  }
+
+  #[test]
+  fn test_proof_blob_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let blob = super::encode_proof_blob(&rlp_nodes);
+    let total_len = rlp_nodes.iter().map(|node| node.len()).sum::<usize>();
+    assert_eq!(blob.len(), total_len + 4 * rlp_nodes.len());
+    assert_eq!(super::decode_proof_blob(&blob), Ok(rlp_nodes));
+
+    assert_eq!(super::decode_proof_blob(&[]), Ok(vec![]));
+  }
+
+  #[test]
+  fn test_decode_proof_blob_rejects_truncated_blob() {
+    let nodes = [
+      alloy_primitives::Bytes::from_static(b"first"),
+      alloy_primitives::Bytes::from_static(b"second"),
+    ];
+    let blob = super::encode_proof_blob(&nodes);
+
+    // Second node starts after the first one and its prefix.
+    let truncated = Err(super::ProofBlobError::Truncated { offset: 9 });
+    assert_eq!(super::decode_proof_blob(&blob[..blob.len() - 1]), truncated);
+    assert_eq!(super::decode_proof_blob(&blob[..11]), truncated);
+  }
+
+  #[test]
+  fn test_reconstruct_with_values_returns_all_items() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let (trie, values) = super::reconstruct_with_values(&rlp_nodes).unwrap();
+    assert_eq!(trie.hash_slow(), root);
+
+    let expected = keys
+      .iter()
+      .map(|(key, value)| (alloy_trie::Nibbles::unpack(key), value.clone()))
+      .collect::<alloy_primitives::map::HashMap<_, _>>();
+    assert_eq!(values, expected);
+  }
+
+  #[test]
+  fn test_from_rlp_checked_reports_corrupted_node() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, mut rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    assert_eq!(
+      super::from_rlp_checked(&rlp_nodes).unwrap().hash_slow(),
+      root
+    );
+
+    // Truncated node no longer matches the length declared by its RLP header.
+    let node = &rlp_nodes[2];
+    rlp_nodes[2] = node.slice(..node.len() - 1);
+    let err = super::from_rlp_checked(&rlp_nodes).unwrap_err();
+    assert_eq!(err.index, 2);
+  }
+
+  #[test]
+  fn test_items_from_proof_roundtrip() {
+    let mut keys = create_test_data(&[("DA01", "1"), ("DA02", "2"), ("EA01", "3"), ("EA02", "4")]);
+    keys.reverse();
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let items = super::items_from_proof(&rlp_nodes).unwrap();
+    keys.sort();
+    let expected = keys
+      .iter()
+      .map(|(key, value)| (key.to_vec(), value.clone()))
+      .collect::<Vec<_>>();
+    assert_eq!(items, expected);
+  }
+}
@@ -0,0 +1,58 @@
+//! Conversion between RLP-encoded proof nodes and decoded alloy-trie nodes.
+//!
+//! Nodes are [`alloy_trie::nodes::TrieNode`], the decoded form used by clients built on
+//! alloy-trie, such as reth. No reth types are involved, so the module needs no reth dependency.
+
+use alloc::vec::Vec;
+use alloy_rlp::Decodable;
+
+/// Decodes RLP-encoded proof nodes into alloy-trie nodes
+///
+/// # Arguments
+/// * `rlp_nodes` - RLP-encoded proof nodes
+///
+/// # Returns
+/// * Decoded nodes, in the same order, or the first decoding error
+pub fn to_reth_nodes(
+  rlp_nodes: &[alloy_primitives::Bytes],
+) -> Result<Vec<alloy_trie::nodes::TrieNode>, alloy_rlp::Error> {
+  rlp_nodes
+    .iter()
+    .map(|rlp| alloy_trie::nodes::TrieNode::decode(&mut rlp.as_ref()))
+    .collect()
+}
+
+/// Encodes alloy-trie nodes into RLP, as returned by the build functions
+///
+/// # Arguments
+/// * `nodes` - Alloy-trie nodes
+///
+/// # Returns
+/// * RLP-encoded nodes, in the same order
+pub fn from_reth_nodes(nodes: &[alloy_trie::nodes::TrieNode]) -> Vec<alloy_primitives::Bytes> {
+  nodes
+    .iter()
+    .map(|node| alloy_primitives::Bytes::from(alloy_rlp::encode(node)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::create_test_data;
+
+  #[test]
+  fn test_reth_nodes_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let reth_nodes = super::to_reth_nodes(&rlp_nodes).unwrap();
+    assert_eq!(reth_nodes.len(), rlp_nodes.len());
+    assert_eq!(super::from_reth_nodes(&reth_nodes), rlp_nodes);
+  }
+
+  #[test]
+  fn test_to_reth_nodes_rejects_malformed_node() {
+    let rlp_nodes = [alloy_primitives::Bytes::from_static(&[0xc1])];
+    assert!(super::to_reth_nodes(&rlp_nodes).is_err());
+  }
+}
@@ -0,0 +1,122 @@
+//! Secure trie, keyed by keccak256 of the raw keys - as used by Ethereum state and storage tries.
+
+use alloc::vec::Vec;
+
+/// Secure trie with proofs for all items, remembering the raw key behind each hashed path
+#[derive(Debug, Clone)]
+pub struct SecureTrieProof {
+  /// Root hash of the trie.
+  root: alloy_primitives::B256,
+  /// RLP-encoded nodes of the trie, root node first.
+  rlp_nodes: Vec<alloy_primitives::Bytes>,
+  /// Hashed key of each raw key inserted into the trie.
+  hashed_keys: alloy_primitives::map::HashMap<alloy_primitives::Bytes, alloy_primitives::B256>,
+}
+
+impl SecureTrieProof {
+  /// Root hash of the trie.
+  pub fn root(&self) -> alloy_primitives::B256 {
+    self.root
+  }
+
+  /// RLP-encoded nodes of the trie, root node first.
+  pub fn rlp_nodes(&self) -> &[alloy_primitives::Bytes] {
+    &self.rlp_nodes
+  }
+
+  /// Unwraps into the same shape as the output of [`crate::build_alloy_trie_with_proof`].
+  pub fn into_build_output(self) -> (alloy_primitives::B256, Vec<alloy_primitives::Bytes>) {
+    (self.root, self.rlp_nodes)
+  }
+
+  /// Hashed key under which given raw key was inserted, if it was.
+  pub fn hashed_key(&self, raw_key: impl AsRef<[u8]>) -> Option<alloy_primitives::B256> {
+    let raw_key = alloy_primitives::Bytes::copy_from_slice(raw_key.as_ref());
+    self.hashed_keys.get(&raw_key).copied()
+  }
+
+  /// Merkle proof for given raw key
+  ///
+  /// # Arguments
+  /// * `raw_key` - Key as originally passed to [`build_secure_trie`], before hashing
+  ///
+  /// # Returns
+  /// * RLP-encoded nodes on the path of the hashed key, root node first, or `None` if the raw key
+  ///   was not inserted
+  pub fn proof(&self, raw_key: impl AsRef<[u8]>) -> Option<Vec<alloy_primitives::Bytes>> {
+    let hashed_key = self.hashed_key(raw_key)?;
+    let path = alloy_trie::Nibbles::unpack(hashed_key);
+    let proof = crate::nodes::path_nodes(&self.rlp_nodes, &path)
+      .expect("nodes built by Alloy are well-formed");
+    Some(proof)
+  }
+}
+
+/// Builds a secure trie with merkle proof for all nodes
+///
+/// Produces the same root and nodes as [`crate::build_alloy_trie_with_proof_hashed`], but keeps
+/// the mapping from raw to hashed keys, so that proofs can be requested by the original key.
+///
+/// # Arguments
+/// * `items` - Key-value pairs to insert into the trie, with raw (not yet hashed) keys
+///
+/// # Returns
+/// * Secure trie with its root, RLP-encoded proof nodes and raw-to-hashed key mapping
+///
+/// # Panics
+/// * If `items` contain duplicate keys
+pub fn build_secure_trie<K: AsRef<[u8]>, V: AsRef<[u8]>>(items: &[(K, V)]) -> SecureTrieProof {
+  let hashed_keys = items
+    .iter()
+    .map(|(k, _)| {
+      let raw_key = alloy_primitives::Bytes::copy_from_slice(k.as_ref());
+      (raw_key, alloy_primitives::keccak256(k))
+    })
+    .collect();
+  let (root, rlp_nodes) = crate::build_alloy_trie_with_proof_hashed(items);
+
+  SecureTrieProof {
+    root,
+    rlp_nodes,
+    hashed_keys,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn test_secure_trie_proves_raw_keys() {
+    let items = [
+      (
+        alloy_primitives::Address::repeat_byte(0x11),
+        b"alice".to_vec(),
+      ),
+      (
+        alloy_primitives::Address::repeat_byte(0x22),
+        b"bob".to_vec(),
+      ),
+      (
+        alloy_primitives::Address::repeat_byte(0x33),
+        b"carol".to_vec(),
+      ),
+    ];
+    let secure_trie = super::build_secure_trie(&items);
+    assert_eq!(
+      secure_trie.clone().into_build_output(),
+      crate::build_alloy_trie_with_proof_hashed(&items)
+    );
+
+    for (raw_key, value) in &items {
+      let hashed_key = secure_trie.hashed_key(raw_key).unwrap();
+      assert_eq!(hashed_key, alloy_primitives::keccak256(raw_key));
+
+      let proof = secure_trie.proof(raw_key).unwrap();
+      let proven_value = crate::verify_key_path(secure_trie.root(), &proof, hashed_key.as_slice());
+      assert_eq!(proven_value, Ok(Some(value.clone())));
+    }
+
+    let unknown_key = alloy_primitives::Address::repeat_byte(0x44);
+    assert_eq!(secure_trie.hashed_key(unknown_key), None);
+    assert_eq!(secure_trie.proof(unknown_key), None);
+  }
+}
@@ -0,0 +1,222 @@
+//! Incremental root computation over a stream of sorted items.
+
+use alloc::{vec, vec::Vec};
+
+/// Builds an Alloy trie from items added one by one, in ascending key order
+///
+/// Unlike [`crate::build_alloy_trie_with_proof`], the root of the items added so far can be
+/// checkpointed at any point. No proof nodes are retained.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingTrieBuilder {
+  /// Alloy trie hasher, fed with leaves as they are added.
+  hash_builder: alloy_trie::HashBuilder,
+  /// Path of the last added key, to enforce the ordering required by the hasher.
+  last_path: Option<alloy_trie::Nibbles>,
+}
+
+impl StreamingTrieBuilder {
+  /// Creates a builder of an empty trie.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a key-value pair to the trie
+  ///
+  /// # Arguments
+  /// * `key` - Key to insert, which must sort after all previously added keys
+  /// * `value` - Value to insert
+  ///
+  /// # Returns
+  /// * `Ok(())` if added, or an error if the key is not greater than the previous one or extends
+  ///   it - the builder is left unchanged in that case
+  pub fn add(
+    &mut self,
+    key: impl AsRef<[u8]>,
+    value: impl AsRef<[u8]>,
+  ) -> Result<(), crate::TrieBuildError> {
+    let path = alloy_trie::Nibbles::unpack(key.as_ref());
+    if let Some(last_path) = self.last_path {
+      let error_key = || alloy_primitives::Bytes::copy_from_slice(key.as_ref());
+      match path.cmp(&last_path) {
+        core::cmp::Ordering::Less => return Err(crate::TrieBuildError::UnsortedKey(error_key())),
+        core::cmp::Ordering::Equal => return Err(crate::TrieBuildError::DuplicateKey(error_key())),
+        core::cmp::Ordering::Greater if path.starts_with(&last_path) => {
+          return Err(crate::TrieBuildError::PrefixKey(error_key()));
+        }
+        core::cmp::Ordering::Greater => {}
+      }
+    }
+
+    self.hash_builder.add_leaf(path, value.as_ref());
+    self.last_path = Some(path);
+    Ok(())
+  }
+
+  /// Root hash of the trie formed by the items added so far
+  ///
+  /// Computing the root finalizes the hasher state, so it is done on a copy - the builder can
+  /// keep accepting items afterwards.
+  pub fn current_root(&self) -> alloy_primitives::B256 {
+    self.hash_builder.clone().root()
+  }
+
+  /// Merkle proof of the last added key, in the trie formed by the items added so far
+  ///
+  /// Nodes on the path of the last key are not finalized by the hasher until another key is
+  /// added, so a copy of it can still retain them - no rebuild is needed.
+  ///
+  /// # Returns
+  /// * RLP-encoded proof nodes, root node first, or no nodes if nothing was added yet
+  pub fn last_key_proof(&self) -> Vec<alloy_primitives::Bytes> {
+    let Some(last_path) = self.last_path else {
+      return Vec::new();
+    };
+
+    let proof_retainer = alloy_trie::proof::ProofRetainer::new(vec![last_path]);
+    let mut hb = self
+      .hash_builder
+      .clone()
+      .with_proof_retainer(proof_retainer);
+    hb.root();
+    hb.take_proof_nodes()
+      .into_nodes_sorted()
+      .into_iter()
+      .map(|(_, rlp)| rlp)
+      .collect()
+  }
+
+  /// Root hash of the trie formed by all added items, consuming the builder.
+  pub fn finish(mut self) -> alloy_primitives::B256 {
+    self.hash_builder.root()
+  }
+}
+
+/// Computes the root of a trie over items streamed in ascending key order
+///
+/// Items are fed into the hasher one by one, so only the hasher state is kept in memory - a
+/// low-memory path for huge tries, e.g. over a key range `[start, end)` of a sorted database.
+///
+/// # Arguments
+/// * `range` - Key-value pairs, sorted by key in ascending order
+///
+/// # Returns
+/// * Root hash, or the empty root for an empty range
+///
+/// # Panics
+/// * If `range` is not sorted, contains duplicate keys or keys being prefixes of other keys
+pub fn root_over_range<K, V, I>(range: I) -> alloy_primitives::B256
+where
+  I: Iterator<Item = (K, V)>,
+  K: AsRef<[u8]>,
+  V: AsRef<[u8]>,
+{
+  let mut builder = StreamingTrieBuilder::new();
+  for (key, value) in range {
+    if let Err(err) = builder.add(key, value) {
+      panic!("invalid range: {err}");
+    }
+  }
+  builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::{create_test_data, key_from_nibbles};
+
+  #[test]
+  fn test_streaming_roots_match_batch_build() {
+    let keys = create_test_data(&[("A0FF", "4"), ("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+
+    let mut builder = super::StreamingTrieBuilder::new();
+    assert_eq!(builder.current_root(), alloy_trie::EMPTY_ROOT_HASH);
+    for (i, (key, value)) in keys.iter().enumerate() {
+      builder.add(key, value).unwrap();
+      assert_eq!(builder.current_root(), crate::alloy_root_only(&keys[..=i]));
+    }
+    assert_eq!(builder.finish(), crate::alloy_root_only(&keys));
+  }
+
+  #[test]
+  fn test_streaming_rejects_unsorted_and_duplicate_keys() {
+    let mut builder = super::StreamingTrieBuilder::new();
+    builder.add(key_from_nibbles("ABC1"), b"1").unwrap();
+    let root = builder.current_root();
+
+    let unsorted_key = key_from_nibbles("A0FF");
+    assert_eq!(
+      builder.add(unsorted_key, b"2"),
+      Err(crate::TrieBuildError::UnsortedKey(
+        alloy_primitives::Bytes::copy_from_slice(unsorted_key.as_slice())
+      ))
+    );
+    let duplicate_key = key_from_nibbles("ABC1");
+    assert_eq!(
+      builder.add(duplicate_key, b"3"),
+      Err(crate::TrieBuildError::DuplicateKey(
+        alloy_primitives::Bytes::copy_from_slice(duplicate_key.as_slice())
+      ))
+    );
+    assert_eq!(builder.current_root(), root);
+  }
+
+  #[test]
+  fn test_streaming_rejects_prefix_keys() {
+    let mut builder = super::StreamingTrieBuilder::new();
+    builder.add([0xAB], b"1").unwrap();
+    assert_eq!(
+      builder.add([0xAB, 0xCD], b"2"),
+      Err(crate::TrieBuildError::PrefixKey(
+        alloy_primitives::Bytes::from_static(&[0xAB, 0xCD])
+      ))
+    );
+    // Sibling of the rejected key is still accepted.
+    builder.add([0xAC], b"3").unwrap();
+  }
+
+  #[test]
+  fn test_last_key_proof_verifies_against_current_root() {
+    let mut builder = super::StreamingTrieBuilder::new();
+    assert!(builder.last_key_proof().is_empty());
+
+    builder.add(key_from_nibbles("A0FF"), b"1").unwrap();
+    builder.add(key_from_nibbles("ABC1"), b"2").unwrap();
+    let key = key_from_nibbles("ABD2");
+    builder.add(key, b"3").unwrap();
+
+    let proof = builder.last_key_proof();
+    assert_eq!(
+      crate::verify_key_path(builder.current_root(), &proof, key.as_slice()),
+      Ok(Some(b"3".to_vec()))
+    );
+    // Root extension `A`, branches `A` and `AB`, and the leaf.
+    assert_eq!(proof.len(), 4);
+  }
+
+  #[test]
+  fn test_root_over_range_matches_batch_build() {
+    let mut keys = create_test_data(&[
+      ("0123", "1"),
+      ("A0FF", "2"),
+      ("ABC1", "3"),
+      ("ABD2", "4"),
+      ("E999", "5"),
+    ]);
+    keys.sort();
+
+    let range = keys.iter().map(|(k, v)| (k.as_slice(), v.as_slice()));
+    assert_eq!(super::root_over_range(range), crate::alloy_root_only(&keys));
+
+    let (start, end) = (key_from_nibbles("A"), key_from_nibbles("E"));
+    let range = keys
+      .iter()
+      .filter(|(k, _)| (start..end).contains(k))
+      .map(|(k, v)| (k.as_slice(), v.as_slice()));
+    assert_eq!(
+      super::root_over_range(range),
+      crate::alloy_root_only(&keys[1..4])
+    );
+
+    let empty_range = core::iter::empty::<(&[u8], &[u8])>();
+    assert_eq!(super::root_over_range(empty_range), crate::EMPTY_ROOT);
+  }
+}
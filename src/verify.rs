@@ -0,0 +1,726 @@
+use alloc::vec::Vec;
+
+/// Errors returned when a proof does not verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+  /// Proof nodes could not be decoded into a Risc0 trie.
+  Rlp(alloy_rlp::Error),
+  /// Proof nodes hash to a different root.
+  RootMismatch {
+    expected: alloy_primitives::B256,
+    actual: alloy_primitives::B256,
+  },
+  /// The key is not present in the trie.
+  KeyNotFound,
+  /// The key is present, but holds a different value.
+  ValueMismatch {
+    expected: alloy_primitives::Bytes,
+    actual: alloy_primitives::Bytes,
+  },
+  /// Node referenced by hash is not among the proof nodes.
+  MissingNode(alloy_primitives::B256),
+  /// Merged proofs hold different nodes at the same path.
+  ConflictingNodes {
+    path: alloy_trie::Nibbles,
+    first: alloy_primitives::B256,
+    second: alloy_primitives::B256,
+  },
+}
+
+impl core::fmt::Display for ProofError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Rlp(err) => write!(f, "invalid proof nodes: {err}"),
+      Self::RootMismatch { expected, actual } => {
+        write!(f, "root mismatch: expected {expected}, got {actual}")
+      }
+      Self::KeyNotFound => write!(f, "key not found in trie"),
+      Self::ValueMismatch { expected, actual } => {
+        write!(f, "value mismatch: expected {expected}, got {actual}")
+      }
+      Self::MissingNode(hash) => write!(f, "node {hash} missing from proof"),
+      Self::ConflictingNodes {
+        path,
+        first,
+        second,
+      } => write!(f, "conflicting nodes {first} and {second} at path {path:?}"),
+    }
+  }
+}
+
+impl core::error::Error for ProofError {
+  fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+    match self {
+      Self::Rlp(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<crate::nodes::NodeError> for ProofError {
+  fn from(err: crate::nodes::NodeError) -> Self {
+    match err {
+      crate::nodes::NodeError::Rlp(err) => Self::Rlp(err),
+      crate::nodes::NodeError::MissingNode(hash) => Self::MissingNode(hash),
+    }
+  }
+}
+
+/// Verifies that proof nodes hash to a known root and contain given key-value pair
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `rlp_nodes` - RLP-encoded proof nodes, root node first
+/// * `key` - Key to look up
+/// * `expected_value` - Value the key should resolve to
+///
+/// # Returns
+/// * `Ok(())` if the proof is valid, or the reason why it is not
+pub fn verify_proof(
+  root: alloy_primitives::B256,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+  expected_value: &[u8],
+) -> Result<(), ProofError> {
+  let trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).map_err(ProofError::Rlp)?;
+
+  let actual = trie.hash_slow();
+  if actual != root {
+    return Err(ProofError::RootMismatch {
+      expected: root,
+      actual,
+    });
+  }
+
+  let value = trie.get(key).ok_or(ProofError::KeyNotFound)?;
+  if value != expected_value {
+    return Err(ProofError::ValueMismatch {
+      expected: alloy_primitives::Bytes::copy_from_slice(expected_value),
+      actual: alloy_primitives::Bytes::copy_from_slice(value),
+    });
+  }
+
+  Ok(())
+}
+
+/// Same as [`verify_proof`], but with nodes referenced by hashes of given hasher
+///
+/// Risc0 trie is bound to keccak256, so the proof is verified by walking its nodes instead, as in
+/// [`verify_key_path_with`].
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `rlp_nodes` - RLP-encoded proof nodes, root node first
+/// * `key` - Key to look up
+/// * `expected_value` - Value the key should resolve to
+///
+/// # Returns
+/// * `Ok(())` if the proof is valid, or the reason why it is not
+pub fn verify_proof_with<H: crate::TrieHasher>(
+  root: alloy_primitives::B256,
+  rlp_nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+  expected_value: &[u8],
+) -> Result<(), ProofError> {
+  let actual = rlp_nodes
+    .first()
+    .map_or_else(H::empty_root, |rlp| H::hash(rlp));
+  if actual != root {
+    return Err(ProofError::RootMismatch {
+      expected: root,
+      actual,
+    });
+  }
+
+  let value = verify_key_path_with::<H>(root, rlp_nodes, key)?.ok_or(ProofError::KeyNotFound)?;
+  if value != expected_value {
+    return Err(ProofError::ValueMismatch {
+      expected: alloy_primitives::Bytes::copy_from_slice(expected_value),
+      actual: alloy_primitives::Bytes::from(value),
+    });
+  }
+
+  Ok(())
+}
+
+/// Looks up a key by walking proof nodes from the root, without building a Risc0 trie
+///
+/// Nodes are followed by hash, so every node on the path is checked to hash to the reference
+/// held by its parent. Cheaper than [`verify_proof`] for single lookups.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `nodes` - RLP-encoded proof nodes, in any order
+/// * `key` - Key to look up
+///
+/// # Returns
+/// * Value of the key if present, `None` for a valid exclusion proof, or an error if the path
+///   leads to a node missing from the proof
+pub fn verify_key_path(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+) -> Result<Option<Vec<u8>>, ProofError> {
+  verify_key_path_with::<crate::KeccakHasher>(root, nodes, key)
+}
+
+/// Same as [`verify_key_path`], but with nodes referenced by hashes of given hasher.
+pub fn verify_key_path_with<H: crate::TrieHasher>(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+) -> Result<Option<Vec<u8>>, ProofError> {
+  if root == H::empty_root() {
+    return Ok(None);
+  }
+
+  let index = crate::nodes::NodeIndex::with_hasher::<H>(nodes);
+  let path = alloy_trie::Nibbles::unpack(key);
+  let mut depth = 0;
+  let mut node = index.node_by_hash(root)?;
+  loop {
+    match node {
+      alloy_trie::nodes::TrieNode::EmptyRoot => return Ok(None),
+      alloy_trie::nodes::TrieNode::Leaf(leaf) => {
+        let found = path.slice(depth..) == leaf.key;
+        return Ok(found.then_some(leaf.value));
+      }
+      alloy_trie::nodes::TrieNode::Extension(extension) => {
+        if !path.slice(depth..).starts_with(&extension.key) {
+          return Ok(None);
+        }
+        depth += extension.key.len();
+        node = index.resolve(&extension.child)?;
+      }
+      alloy_trie::nodes::TrieNode::Branch(branch) => {
+        // Path ending at a branch would require a branch value, which is never set.
+        let Some(nibble) = path.get(depth) else {
+          return Ok(None);
+        };
+        let Some(child) = crate::nodes::branch_child(&branch, nibble) else {
+          return Ok(None);
+        };
+        depth += 1;
+        node = index.resolve(child)?;
+      }
+    }
+  }
+}
+
+/// Verifies that proof nodes with a known root hold a value with given hash under the key
+///
+/// Leaf nodes embed their values, so the proof still carries the full value - but the verifier
+/// only needs to know its hash upfront.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `nodes` - RLP-encoded proof nodes, in any order
+/// * `key` - Key to look up
+/// * `value_hash` - Keccak256 of the value the key should resolve to
+///
+/// # Returns
+/// * `true` if the key is present and its value hashes to `value_hash`
+pub fn verify_value_hash(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+  value_hash: alloy_primitives::B256,
+) -> bool {
+  matches!(
+    verify_key_path(root, nodes, key),
+    Ok(Some(value)) if alloy_primitives::keccak256(&value) == value_hash
+  )
+}
+
+/// Verifies proof nodes with a known root and returns the raw value stored under the key
+///
+/// Value is only returned once the path to it is verified, so it cannot be read from an
+/// unverified proof by mistake. For storage and account tries, the value is RLP to be decoded by
+/// the caller.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `nodes` - RLP-encoded proof nodes, in any order
+/// * `key` - Key to look up
+///
+/// # Returns
+/// * Value of the key, or an error if the proof is invalid or proves the key absent
+pub fn proven_value(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+) -> Result<Vec<u8>, ProofError> {
+  verify_key_path(root, nodes, key)?.ok_or(ProofError::KeyNotFound)
+}
+
+/// Verifies the hash chain from a known root along the path of a key, returning its nodes
+///
+/// Only nodes referenced by hash are returned - nodes shorter than 32 bytes are inlined into
+/// their parents, so they are already part of the parent's RLP. Hashing the returned nodes
+/// bottom-up, each hash is found in the node preceding it, and the first one hashes to `root`.
+/// The path ends where the key resolves, so for an absent key the nodes prove exclusion.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie
+/// * `nodes` - RLP-encoded proof nodes, in any order
+/// * `key` - Key whose path to follow
+///
+/// # Returns
+/// * Kind and RLP of each node on the path, root node first, no nodes for the empty root, or an
+///   error if the path leads to a node missing from the proof
+pub fn authenticated_path(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+) -> Result<Vec<(crate::NodeKind, alloy_primitives::Bytes)>, ProofError> {
+  if root == alloy_trie::EMPTY_ROOT_HASH {
+    return Ok(Vec::new());
+  }
+
+  let index = crate::nodes::NodeIndex::new(nodes);
+  let path = alloy_trie::Nibbles::unpack(key);
+  let mut authenticated = Vec::new();
+  let mut depth = 0;
+  let mut child = alloy_trie::nodes::RlpNode::word_rlp(&root);
+  loop {
+    let node = match child.as_hash() {
+      Some(hash) => {
+        let rlp = index.rlp_by_hash(hash)?;
+        let node = crate::nodes::decode(rlp)?;
+        authenticated.push((
+          crate::NodeKind::from(&node),
+          alloy_primitives::Bytes::copy_from_slice(rlp),
+        ));
+        node
+      }
+      None => crate::nodes::decode(&child)?,
+    };
+
+    child = match node {
+      alloy_trie::nodes::TrieNode::EmptyRoot | alloy_trie::nodes::TrieNode::Leaf(_) => break,
+      alloy_trie::nodes::TrieNode::Extension(extension) => {
+        if !path.slice(depth..).starts_with(&extension.key) {
+          break;
+        }
+        depth += extension.key.len();
+        extension.child
+      }
+      alloy_trie::nodes::TrieNode::Branch(branch) => {
+        let Some(child) = path
+          .get(depth)
+          .and_then(|nibble| crate::nodes::branch_child(&branch, nibble))
+        else {
+          break;
+        };
+        depth += 1;
+        child.clone()
+      }
+    };
+  }
+  Ok(authenticated)
+}
+
+/// Looks up the value of a key in a Risc0 trie
+///
+/// # Arguments
+/// * `trie` - Risc0 trie, e.g. reconstructed with `Trie::from_rlp`
+/// * `key` - Key to look up
+///
+/// # Returns
+/// * Copy of the value, or `None` if the key is absent
+pub fn get_value(trie: &risc0_ethereum_trie::Trie, key: impl AsRef<[u8]>) -> Option<Vec<u8>> {
+  trie.get(key).map(<[u8]>::to_vec)
+}
+
+/// Inserts a key-value pair into a Risc0 trie and checks the resulting root
+///
+/// Counterpart of the removal checks: `expected_new_root` should be computed independently, e.g.
+/// by building an Alloy trie from the updated key set.
+///
+/// # Arguments
+/// * `trie` - Risc0 trie to insert into
+/// * `key` - Key to insert
+/// * `value` - Value to insert
+/// * `expected_new_root` - Root hash the trie should have after insertion
+///
+/// # Returns
+/// * `Ok(())` if roots match, or [`ProofError::RootMismatch`] otherwise
+pub fn insert_and_verify(
+  trie: &mut risc0_ethereum_trie::Trie,
+  key: &[u8],
+  value: &[u8],
+  expected_new_root: alloy_primitives::B256,
+) -> Result<(), ProofError> {
+  trie.insert(key, value.to_vec());
+
+  let actual = trie.hash_slow();
+  if actual != expected_new_root {
+    return Err(ProofError::RootMismatch {
+      expected: expected_new_root,
+      actual,
+    });
+  }
+
+  Ok(())
+}
+
+/// Computes the root after changing the value of a proven key, without the full item set
+///
+/// The path of `key` is verified against `root` first, then the (possibly partial) trie is
+/// reconstructed with Risc0, the leaf overwritten and the trie rehashed. Only the proof of `key`
+/// is needed, as the update does not change the trie structure.
+///
+/// # Arguments
+/// * `root` - Expected root hash of the trie before the update
+/// * `nodes` - RLP-encoded proof nodes, root node first
+/// * `key` - Key whose value changes
+/// * `new_value` - Value replacing the current one
+///
+/// # Returns
+/// * Root hash after the update, or an error if the proof is invalid, does not cover `key`, or
+///   proves it absent
+pub fn update_leaf_value(
+  root: alloy_primitives::B256,
+  nodes: &[alloy_primitives::Bytes],
+  key: &[u8],
+  new_value: &[u8],
+) -> Result<alloy_primitives::B256, ProofError> {
+  // Risc0 would run into an unresolved node if the proof does not cover `key`, so the path is
+  // verified by walking the nodes first.
+  proven_value(root, nodes, key)?;
+
+  let mut trie = risc0_ethereum_trie::Trie::from_rlp(nodes).map_err(ProofError::Rlp)?;
+  trie.insert(key, new_value.to_vec());
+  Ok(trie.hash_slow())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::{create_test_data, key_from_nibbles};
+
+  #[test]
+  fn test_verify_proof_accepts_valid_proof() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    for (key, value) in &keys {
+      assert_eq!(
+        super::verify_proof(root, &rlp_nodes, key.as_slice(), value),
+        Ok(())
+      );
+    }
+  }
+
+  #[test]
+  fn test_verify_proof_reports_root_mismatch() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let wrong_root = alloy_trie::EMPTY_ROOT_HASH;
+    let result = super::verify_proof(wrong_root, &rlp_nodes, keys[0].0.as_slice(), b"1");
+    assert_eq!(
+      result,
+      Err(super::ProofError::RootMismatch {
+        expected: wrong_root,
+        actual: root,
+      })
+    );
+  }
+
+  #[test]
+  fn test_verify_proof_reports_value_mismatch() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let result = super::verify_proof(root, &rlp_nodes, keys[0].0.as_slice(), b"2");
+    assert_eq!(
+      result,
+      Err(super::ProofError::ValueMismatch {
+        expected: alloy_primitives::Bytes::from_static(b"2"),
+        actual: alloy_primitives::Bytes::from_static(b"1"),
+      })
+    );
+  }
+
+  #[test]
+  fn test_verify_proof_reports_missing_key() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, _) = crate::build_alloy_trie_with_proof(&keys);
+    let absent_key = key_from_nibbles("A0FF");
+    let rlp_nodes = crate::build_exclusion_proof(&keys, &absent_key);
+    let result = super::verify_proof(root, &rlp_nodes, absent_key.as_slice(), b"1");
+    assert_eq!(result, Err(super::ProofError::KeyNotFound));
+  }
+
+  #[test]
+  fn test_authenticated_path_hashes_up_to_root() {
+    // Values as long as hashes keep all nodes from being inlined.
+    let keys = create_test_data(&[("A0FF", "1"), ("ABC1", "2"), ("ABD2", "3"), ("E999", "4")])
+      .into_iter()
+      .map(|(key, value)| (key, alloy_primitives::keccak256(value).to_vec()))
+      .collect::<Vec<_>>();
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let path =
+      super::authenticated_path(root, &rlp_nodes, key_from_nibbles("ABD2").as_slice()).unwrap();
+    let kinds = path.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+    // Root branch, branches `A` and `AB`, and the leaf.
+    assert_eq!(
+      kinds,
+      [
+        crate::NodeKind::Branch,
+        crate::NodeKind::Branch,
+        crate::NodeKind::Branch,
+        crate::NodeKind::Leaf
+      ]
+    );
+
+    let mut hash = None;
+    for (_, rlp) in path.iter().rev() {
+      if let Some(child_hash) = hash {
+        assert!(
+          rlp
+            .windows(32)
+            .any(|window| window == child_hash.as_slice())
+        );
+      }
+      hash = Some(alloy_primitives::keccak256(rlp));
+    }
+    assert_eq!(hash, Some(root));
+
+    let wrong_root = alloy_primitives::keccak256(b"wrong root");
+    assert_eq!(
+      super::authenticated_path(wrong_root, &rlp_nodes, key_from_nibbles("ABD2").as_slice()),
+      Err(super::ProofError::MissingNode(wrong_root))
+    );
+  }
+
+  #[test]
+  fn test_insert_and_verify_matches_alloy_build() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).unwrap();
+
+    let insertee_key = key_from_nibbles("A0FF");
+    let mut keys_with_insertee = keys.clone();
+    keys_with_insertee.push((insertee_key, b"insertee".to_vec()));
+    let (expected_root, _) = crate::build_alloy_trie_with_proof(&keys_with_insertee);
+
+    let result = super::insert_and_verify(
+      &mut r0_trie,
+      insertee_key.as_slice(),
+      b"insertee",
+      expected_root,
+    );
+    assert_eq!(result, Ok(()));
+  }
+
+  #[test]
+  fn test_insert_and_verify_reports_root_mismatch() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let mut r0_trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).unwrap();
+
+    // Expecting the unchanged root must fail, since insertion changes the trie.
+    let insertee_key = key_from_nibbles("A0FF");
+    let result = super::insert_and_verify(&mut r0_trie, insertee_key.as_slice(), b"insertee", root);
+    assert!(
+      matches!(result, Err(super::ProofError::RootMismatch { expected, .. }) if expected == root)
+    );
+  }
+
+  #[test]
+  fn test_verify_key_path_finds_included_keys() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    for (key, value) in &keys {
+      let result = super::verify_key_path(root, &rlp_nodes, key.as_slice());
+      assert_eq!(result, Ok(Some(value.clone())));
+    }
+  }
+
+  #[test]
+  fn test_verify_key_path_accepts_exclusion_proof() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, _) = crate::build_alloy_trie_with_proof(&keys);
+    for absent_key in [
+      key_from_nibbles("A0FF"),
+      key_from_nibbles("ABE0"),
+      key_from_nibbles("F000"),
+    ] {
+      let rlp_nodes = crate::build_exclusion_proof(&keys, &absent_key);
+      let result = super::verify_key_path(root, &rlp_nodes, absent_key.as_slice());
+      assert_eq!(result, Ok(None));
+    }
+  }
+
+  #[test]
+  fn test_verify_key_path_reports_missing_node() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    // Proof for a different key does not contain the path of E999.
+    let (_, partial_nodes) =
+      crate::build_alloy_trie_with_selective_proof(&keys, &[key_from_nibbles("ABC1")]);
+    let result = super::verify_key_path(root, &partial_nodes, key_from_nibbles("E999").as_slice());
+    assert!(matches!(result, Err(super::ProofError::MissingNode(_))));
+
+    // Wrong root does not resolve to any node.
+    let wrong_root = alloy_primitives::keccak256(b"wrong root");
+    let result = super::verify_key_path(wrong_root, &rlp_nodes, keys[0].0.as_slice());
+    assert_eq!(result, Err(super::ProofError::MissingNode(wrong_root)));
+  }
+
+  #[test]
+  fn test_get_value_after_rlp_roundtrip() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (_, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let r0_trie = risc0_ethereum_trie::Trie::from_rlp(rlp_nodes).unwrap();
+
+    assert_eq!(
+      super::get_value(&r0_trie, key_from_nibbles("ABD2")),
+      Some(b"2".to_vec())
+    );
+    assert_eq!(super::get_value(&r0_trie, key_from_nibbles("A0FF")), None);
+  }
+
+  /// Keccak256 with a domain prefix, standing in for an alternative hash function.
+  struct PrefixedKeccakHasher;
+
+  impl crate::TrieHasher for PrefixedKeccakHasher {
+    fn hash(rlp: &[u8]) -> alloy_primitives::B256 {
+      alloy_primitives::keccak256([b"prefix".as_slice(), rlp].concat())
+    }
+  }
+
+  #[test]
+  fn test_verify_with_keccak_hasher_matches_default() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    for (key, value) in &keys {
+      assert_eq!(
+        super::verify_proof_with::<crate::KeccakHasher>(root, &rlp_nodes, key.as_slice(), value),
+        super::verify_proof(root, &rlp_nodes, key.as_slice(), value)
+      );
+    }
+
+    let wrong_value = b"4";
+    assert_eq!(
+      super::verify_proof_with::<crate::KeccakHasher>(
+        root,
+        &rlp_nodes,
+        keys[0].0.as_slice(),
+        wrong_value
+      ),
+      super::verify_proof(root, &rlp_nodes, keys[0].0.as_slice(), wrong_value)
+    );
+  }
+
+  #[test]
+  fn test_verify_with_alternative_hasher() {
+    // Single leaf has no children referenced by hash, so it is valid trie under any hash function.
+    let keys = create_test_data(&[("ABC1", "1")]);
+    let (keccak_root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let (key, value) = &keys[0];
+
+    let root = <PrefixedKeccakHasher as crate::TrieHasher>::hash(&rlp_nodes[0]);
+    assert_ne!(root, keccak_root);
+    assert_eq!(
+      super::verify_proof_with::<PrefixedKeccakHasher>(root, &rlp_nodes, key.as_slice(), value),
+      Ok(())
+    );
+    assert_eq!(
+      super::verify_key_path_with::<PrefixedKeccakHasher>(keccak_root, &rlp_nodes, key.as_slice()),
+      Err(super::ProofError::MissingNode(keccak_root))
+    );
+    assert_eq!(
+      super::verify_key_path_with::<PrefixedKeccakHasher>(
+        <PrefixedKeccakHasher as crate::TrieHasher>::empty_root(),
+        &[],
+        key.as_slice()
+      ),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn test_verify_value_hash_of_large_value() {
+    let large_value = vec![0xAB; 1024];
+    let keys = vec![
+      (key_from_nibbles("ABC1"), large_value.clone()),
+      (key_from_nibbles("E999"), b"small".to_vec()),
+    ];
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+    let key = key_from_nibbles("ABC1");
+
+    let value_hash = alloy_primitives::keccak256(&large_value);
+    assert!(super::verify_value_hash(
+      root,
+      &rlp_nodes,
+      key.as_slice(),
+      value_hash
+    ));
+
+    let other_hash = alloy_primitives::keccak256(&large_value[1..]);
+    assert!(!super::verify_value_hash(
+      root,
+      &rlp_nodes,
+      key.as_slice(),
+      other_hash
+    ));
+
+    let absent_key = key_from_nibbles("A0FF");
+    assert!(!super::verify_value_hash(
+      root,
+      &rlp_nodes,
+      absent_key.as_slice(),
+      value_hash
+    ));
+  }
+
+  #[test]
+  fn test_proven_value_of_included_and_excluded_keys() {
+    let keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("E999", "3")]);
+    let (root, rlp_nodes) = crate::build_alloy_trie_with_proof(&keys);
+
+    let key = key_from_nibbles("ABD2");
+    assert_eq!(
+      super::proven_value(root, &rlp_nodes, key.as_slice()),
+      Ok(b"2".to_vec())
+    );
+
+    let absent_key = key_from_nibbles("A0FF");
+    assert_eq!(
+      super::proven_value(root, &rlp_nodes, absent_key.as_slice()),
+      Err(super::ProofError::KeyNotFound)
+    );
+
+    let wrong_root = alloy_primitives::keccak256(b"wrong root");
+    assert!(super::proven_value(wrong_root, &rlp_nodes, key.as_slice()).is_err());
+  }
+
+  #[test]
+  fn test_update_leaf_value_matches_full_rebuild() {
+    let mut keys = create_test_data(&[("ABC1", "1"), ("ABD2", "2"), ("A0FF", "3"), ("E999", "4")]);
+    let (root, _) = crate::build_alloy_trie_with_proof(&keys);
+    let key = key_from_nibbles("ABD2");
+    let proof = crate::branch_for_key(&keys, &key);
+
+    let new_root = super::update_leaf_value(root, &proof, key.as_slice(), b"updated").unwrap();
+    keys[1].1 = b"updated".to_vec();
+    assert_eq!(new_root, crate::alloy_root_only(&keys));
+
+    // Empty slot of branch `AB`, which is part of the proof.
+    let absent_key = key_from_nibbles("ABE0");
+    assert_eq!(
+      super::update_leaf_value(root, &proof, absent_key.as_slice(), b"updated"),
+      Err(super::ProofError::KeyNotFound)
+    );
+
+    // Proof of another key references branch `AB` by hash only.
+    let other_proof = crate::branch_for_key(&keys, &key_from_nibbles("A0FF"));
+    assert_eq!(
+      super::update_leaf_value(root, &other_proof, key.as_slice(), b"updated"),
+      Err(super::ProofError::MissingNode(alloy_primitives::keccak256(
+        &proof[2]
+      )))
+    );
+  }
+}